@@ -0,0 +1,251 @@
+#![allow(clippy::needless_return)]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use lambda_parser::lexer::lexer::{Lexer, Token};
+use lambda_parser::parser::parser::Parser;
+use lambda_parser::pratt::pratt::PrattParser;
+
+/// The system allocator, counting calls to `alloc` and `realloc` and the bytes asked for.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        return System.alloc(layout);
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(new_size.saturating_sub(layout.size()), Ordering::Relaxed);
+        return System.realloc(ptr, layout, new_size);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Small, deeply nested and very long inputs, by name.
+fn inputs() -> Vec<(&'static str, String)> {
+    let small = "lambda x, y: not x or y * 2 == 3".to_string();
+
+    // The lambda and 190 parentheses: 191 levels, just under the default depth limit of 200.
+    let nested = format!("lambda x: {}1{}", "(x + ".repeat(190), ")".repeat(190));
+
+    let mut long = "lambda alpha, beta, gamma: ".to_string();
+    for i in 0..2000 {
+        long += &format!("(alpha_{} + {} * beta // 3 == gamma % 7) and not delta or True and ", i, i);
+    }
+    long += "False";
+
+    let inputs = vec![("small", small), ("nested", nested), ("long", long)];
+    // Error recovery would be timed instead of parsing.
+    for (name, src) in &inputs {
+        assert_eq!(lex_owned(src), lex(src), "{}", name);
+        let mut parser = Parser::get(src);
+        parser.parse();
+        assert!(parser.diagnostics.is_empty(), "{}: {:?}", name, parser.diagnostics);
+        let mut pratt = PrattParser::get(src);
+        pratt.parse();
+        assert!(pratt.diagnostics.is_empty(), "{}: {:?}", name, pratt.diagnostics);
+    }
+    return inputs;
+}
+
+/// The lexer as it was before tokens borrowed from the source, for comparison: it
+/// owns a copy of the source, allocates the text of every name and number, builds
+/// the keyword map for every word and clones the token on every `get_token`. Only
+/// what a valid input goes through is kept.
+mod owned {
+    use std::collections::HashMap;
+
+    use lambda_parser::lexer::lexer::Operations::{self, Add, And, Div, Mod, Mul, Not, Or, Sub};
+
+    #[derive(Debug, Eq, PartialEq, Clone)]
+    pub enum Constant {
+        True,
+        False,
+        Number(String),
+    }
+
+    #[derive(Debug, Eq, PartialEq, Clone)]
+    pub enum Token {
+        End,
+        Lambda,
+        Colon,
+        Comma,
+        LParen,
+        RParen,
+        Variable(String),
+        Op(Operations),
+        Const(Constant),
+    }
+
+    pub struct Lexer {
+        cur_char: u8,
+        cur_pos: usize,
+        char_pos: usize,
+        cur_token: Token,
+        str: String,
+    }
+
+    impl Lexer {
+        pub fn get(str: String) -> Lexer {
+            let mut x = Lexer { cur_char: 0, cur_pos: 0, char_pos: 0, cur_token: Token::End, str };
+            x.next_char();
+            x.next_token();
+            return x;
+        }
+
+        fn is_separator(c: u8) -> bool {
+            let seps = ['\0', '*', '/', '+', '-', '%', ',', ':', '=', '(', ')'];
+            return c == b' ' || c == b'\r' || c == b'\n' || seps.contains(&(c as char));
+        }
+
+        fn is_word(c: u8) -> bool {
+            return c.is_ascii_alphanumeric() || c == b'_' || c >= 0x80;
+        }
+
+        fn next_char(&mut self) {
+            if self.cur_pos >= self.str.len() {
+                self.cur_char = 0;
+                self.char_pos = self.str.len();
+                return;
+            }
+            self.cur_char = self.str.as_bytes()[self.cur_pos];
+            self.char_pos = self.cur_pos;
+            self.cur_pos += 1;
+        }
+
+        pub fn next_token(&mut self) {
+            while matches!(self.cur_char, b' ' | b'\r' | b'\n') {
+                self.next_char();
+            }
+            let start = self.char_pos;
+            if Lexer::is_separator(self.cur_char) {
+                self.cur_token = match self.cur_char {
+                    0 => Token::End,
+                    b',' => Token::Comma,
+                    b':' => Token::Colon,
+                    b'(' => Token::LParen,
+                    b')' => Token::RParen,
+                    b'*' => Token::Op(Mul),
+                    b'+' => Token::Op(Add),
+                    b'-' => Token::Op(Sub),
+                    b'%' => Token::Op(Mod),
+                    b'/' => {
+                        self.next_char();
+                        Token::Op(Div)
+                    }
+                    _ => {
+                        self.next_char();
+                        Token::Op(Operations::Eq)
+                    }
+                };
+                self.next_char();
+                return;
+            }
+
+            let is_literal = self.cur_char.is_ascii_alphabetic() || self.cur_char == b'_';
+            while Lexer::is_word(self.cur_char) {
+                self.next_char();
+            }
+            let cur_tok = self.str[start..self.char_pos].to_string();
+            if is_literal {
+                let keywords = HashMap::from([
+                    ("lambda", Token::Lambda),
+                    ("not", Token::Op(Not)),
+                    ("and", Token::Op(And)),
+                    ("or", Token::Op(Or)),
+                    ("True", Token::Const(Constant::True)),
+                    ("False", Token::Const(Constant::False)),
+                ]);
+                self.cur_token = match keywords.get(cur_tok.as_str()) {
+                    None => Token::Variable(cur_tok),
+                    Some(tok) => tok.clone(),
+                };
+            } else {
+                self.cur_token = Token::Const(Constant::Number(cur_tok));
+            }
+        }
+
+        pub fn get_token(&self) -> Token {
+            return self.cur_token.clone();
+        }
+    }
+}
+
+/// Parses a source, returning something that depends on the whole result.
+type Stage = fn(&str) -> usize;
+
+fn lex(src: &str) -> usize {
+    let mut lexer = Lexer::get(src);
+    let mut count = 0;
+    while lexer.get_token() != Token::End {
+        lexer.next_token();
+        count += 1;
+    }
+    return count;
+}
+
+/// `lex` with the lexer of `owned`, which is handed a `String` as `Lexer::get` was.
+fn lex_owned(src: &str) -> usize {
+    let mut lexer = owned::Lexer::get(src.to_string());
+    let mut count = 0;
+    while lexer.get_token() != owned::Token::End {
+        lexer.next_token();
+        count += 1;
+    }
+    return count;
+}
+
+/// The derivation tree, as `lambda-parser` builds it for the DOT output.
+fn parse(src: &str) -> usize {
+    let mut parser = Parser::get(src);
+    parser.parse();
+    return parser.graph.node_count();
+}
+
+/// The AST alone, with no derivation tree in between.
+fn parse_ast(src: &str) -> usize {
+    let mut parser = PrattParser::get(src);
+    parser.parse();
+    return parser.ast.len();
+}
+
+/// Criterion measures time only: allocations are counted on one run of every case.
+fn allocations(stage: &str, name: &str, src: &str, run: Stage) {
+    let (allocations, bytes) = (ALLOCATIONS.load(Ordering::Relaxed), BYTES.load(Ordering::Relaxed));
+    black_box(run(black_box(src)));
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    let bytes = BYTES.load(Ordering::Relaxed) - bytes;
+    println!("{}/{}: {} allocations, {} bytes per parse of {} bytes", stage, name, allocations, bytes, src.len());
+}
+
+fn throughput(c: &mut Criterion) {
+    let stages: [(&str, Stage); 4] = [("lexer", lex), ("lexer-owned", lex_owned), ("parser", parse), ("ast", parse_ast)];
+    let inputs = inputs();
+    for (stage, run) in stages {
+        let mut group = c.benchmark_group(stage);
+        for (name, src) in &inputs {
+            allocations(stage, name, src, run);
+            group.throughput(Throughput::BytesDecimal(src.len() as u64));
+            group.bench_with_input(BenchmarkId::from_parameter(name), src.as_str(), |b, src| b.iter(|| run(black_box(src))));
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, throughput);
+criterion_main!(benches);
@@ -0,0 +1,29 @@
+#![no_main]
+
+//! Grammar-generated lambdas, valid or mutated, reach far deeper into the parser than
+//! random bytes do.
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+use lambda_parser::generator::generator::{Generator, GeneratorOptions};
+use lambda_parser::parser::parser::Parser;
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    seed: u64,
+    max_depth: u8,
+    mutate: bool,
+}
+
+fuzz_target!(|input: Input| {
+    let options = GeneratorOptions { seed: input.seed, max_depth: input.max_depth as usize, ..Default::default() };
+    let mut generator = Generator::new(options);
+    let src = if input.mutate { generator.mutant() } else { generator.lambda() };
+    if !input.mutate {
+        let mut parser = Parser::get(&src);
+        parser.parse();
+        assert!(parser.diagnostics.is_empty(), "generated {:?} does not parse", src);
+    }
+    lambda_parser_fuzz::check_parser(&src);
+});
@@ -0,0 +1,49 @@
+#![allow(clippy::needless_return)]
+
+//! Properties every fuzz target checks. A panic or a stack overflow anywhere below is
+//! a crash for libFuzzer.
+
+use lambda_parser::lexer::lexer::Lexer;
+use lambda_parser::lower::lower::lower;
+use lambda_parser::parser::parser::Parser;
+use lambda_parser::pratt::pratt::PrattParser;
+use lambda_parser::unparser::unparser::unparse;
+
+/// Token spans are in order, within the source and on character boundaries.
+pub fn check_lexer(src: &str) {
+    let mut end = 0;
+    for token in Lexer::get(src).flatten() {
+        let span = token.span;
+        assert!(end <= span.start && span.start <= span.end && span.end <= src.len(), "{:?} after {}", span, end);
+        assert!(src.is_char_boundary(span.start) && src.is_char_boundary(span.end), "{:?}", span);
+        end = span.end;
+    }
+}
+
+/// The three parsers agree, and an input without errors survives parse → unparse → parse.
+pub fn check_parser(src: &str) {
+    let mut descent = Parser::get(src);
+    let root = descent.parse();
+
+    let mut table = Parser::get(src);
+    let table_root = table.parse_table();
+    assert_eq!(descent.diagnostics, table.diagnostics, "descent and table parsers disagree on {:?}", src);
+    assert_eq!(unparse(&descent.graph, root), unparse(&table.graph, table_root));
+
+    let ast = lower(&descent, root).ast;
+    assert!(ast == lower(&table, table_root).ast, "descent and table parsers build different trees for {:?}", src);
+    let mut pratt = PrattParser::get(src);
+    pratt.parse();
+    assert_eq!(descent.diagnostics, pratt.diagnostics, "descent and Pratt parsers disagree on {:?}", src);
+    assert!(ast == pratt.ast, "{} != {} for {:?}", ast, pratt.ast, src);
+
+    if descent.diagnostics.iter().any(|d| d.is_error()) {
+        return;
+    }
+    let text = unparse(&descent.graph, root);
+    let mut again = Parser::get(&text);
+    let again_root = again.parse();
+    assert!(!again.diagnostics.iter().any(|d| d.is_error()), "{:?} unparsed to {:?}, which does not parse", src, text);
+    assert_eq!(text, unparse(&again.graph, again_root), "unparse is not a fixed point");
+    assert_eq!(ast.to_string(), lower(&again, again_root).ast.to_string(), "{:?} unparsed to {:?}", src, text);
+}
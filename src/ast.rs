@@ -0,0 +1,289 @@
+pub mod ast {
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::mem;
+    use std::sync::Arc;
+
+    use crate::lexer::lexer::{Operations, Span};
+
+    /// Index of a node in the arena of its `Ast`.
+    #[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, Ord, PartialOrd)]
+    pub struct NodeId(u32);
+
+    impl NodeId {
+        /// Position in the arena, for tables kept next to the tree.
+        pub fn index(self) -> usize {
+            return self.0 as usize;
+        }
+    }
+
+    /// An interned identifier or number literal.
+    #[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, Ord, PartialOrd)]
+    pub struct Symbol(u32);
+
+    /// A run of nodes or parameters stored next to the arena.
+    #[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+    pub struct List {
+        start: u32,
+        len: u32,
+    }
+
+    /// A node of the abstract syntax tree. Shapes follow Python's `ast` module, so
+    /// `a or b or c` is one `BoolOp` and `a == b == c` one `Compare`.
+    #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+    pub enum Node {
+        /// `lambda params: body`
+        Lambda { params: List, body: NodeId },
+        /// `or` or `and` over two values or more.
+        BoolOp { op: Operations, values: List },
+        /// `left == c1 == c2 ...`, `==` being the only comparison.
+        Compare { left: NodeId, comparators: List },
+        /// `+`, `-`, `*`, `//` or `%`.
+        BinOp { left: NodeId, op: Operations, right: NodeId },
+        /// `not`, `+` or `-`.
+        UnaryOp { op: Operations, operand: NodeId },
+        Name(Symbol),
+        Number(Symbol),
+        True,
+        False,
+        /// A part of the source that could not be parsed.
+        Error,
+    }
+
+    /// Identifiers and numbers, each stored once however often it occurs.
+    #[derive(Debug, Clone, Default)]
+    pub struct Interner {
+        names: Vec<Arc<str>>,
+        index: HashMap<Arc<str>, Symbol>,
+    }
+
+    impl Interner {
+        pub fn intern(&mut self, name: &str) -> Symbol {
+            if let Some(symbol) = self.index.get(name) {
+                return *symbol;
+            }
+            let symbol = Symbol(index(self.names.len()));
+            let name: Arc<str> = Arc::from(name);
+            self.names.push(name.clone());
+            self.index.insert(name, symbol);
+            return symbol;
+        }
+
+        pub fn resolve(&self, symbol: Symbol) -> &str {
+            return &self.names[symbol.0 as usize];
+        }
+
+        pub fn len(&self) -> usize {
+            return self.names.len();
+        }
+
+        pub fn is_empty(&self) -> bool {
+            return self.names.is_empty();
+        }
+    }
+
+    fn index(i: usize) -> u32 {
+        return u32::try_from(i).expect("more than u32::MAX nodes");
+    }
+
+    /// An abstract syntax tree in an arena: nodes refer to each other by `NodeId`,
+    /// so a node takes 16 bytes plus its span, and cloning or dropping the tree
+    /// is a handful of `Vec` copies or frees.
+    #[derive(Debug, Clone, Default)]
+    pub struct Ast {
+        nodes: Vec<Node>,
+        spans: Vec<Span>,
+        /// Values of `BoolOp` and comparators of `Compare`.
+        lists: Vec<NodeId>,
+        /// Parameters of `Lambda`.
+        params: Vec<Symbol>,
+        pub symbols: Interner,
+        root: Option<NodeId>,
+    }
+
+    impl Ast {
+        pub fn new() -> Ast {
+            return Ast::default();
+        }
+
+        /// Adds a node; its children must have been added before.
+        pub fn add(&mut self, node: Node, span: Span) -> NodeId {
+            let id = NodeId(index(self.nodes.len()));
+            self.nodes.push(node);
+            self.spans.push(span);
+            return id;
+        }
+
+        /// Puts `node` in place of the node at `id`, e.g. to rename a variable.
+        pub fn replace(&mut self, id: NodeId, node: Node) {
+            self.nodes[id.0 as usize] = node;
+        }
+
+        pub fn list(&mut self, items: &[NodeId]) -> List {
+            let list = List { start: index(self.lists.len()), len: index(items.len()) };
+            self.lists.extend_from_slice(items);
+            return list;
+        }
+
+        pub fn param_list(&mut self, params: &[Symbol]) -> List {
+            let list = List { start: index(self.params.len()), len: index(params.len()) };
+            self.params.extend_from_slice(params);
+            return list;
+        }
+
+        pub fn intern(&mut self, name: &str) -> Symbol {
+            return self.symbols.intern(name);
+        }
+
+        pub fn set_root(&mut self, root: NodeId) {
+            self.root = Some(root);
+        }
+
+        /// The lambda the whole source is; `None` until the tree is complete.
+        pub fn root(&self) -> Option<NodeId> {
+            return self.root;
+        }
+
+        pub fn node(&self, id: NodeId) -> Node {
+            return self.nodes[id.0 as usize];
+        }
+
+        pub fn span(&self, id: NodeId) -> Span {
+            return self.spans[id.0 as usize];
+        }
+
+        pub fn items(&self, list: List) -> &[NodeId] {
+            return &self.lists[list.start as usize..(list.start + list.len) as usize];
+        }
+
+        pub fn params(&self, list: List) -> &[Symbol] {
+            return &self.params[list.start as usize..(list.start + list.len) as usize];
+        }
+
+        pub fn name(&self, symbol: Symbol) -> &str {
+            return self.symbols.resolve(symbol);
+        }
+
+        pub fn len(&self) -> usize {
+            return self.nodes.len();
+        }
+
+        pub fn is_empty(&self) -> bool {
+            return self.nodes.is_empty();
+        }
+
+        /// Child nodes from left to right.
+        ///
+        /// A long operator chain makes a tree as deep as the chain is long, and a sum of a
+        /// million terms would overflow any recursive walk. So every walk of a tree, this
+        /// one or a derivation tree, keeps its own stack of the nodes left to visit.
+        pub fn children(&self, id: NodeId) -> Vec<NodeId> {
+            return match self.node(id) {
+                Node::Lambda { body, .. } => vec![body],
+                Node::BoolOp { values, .. } => self.items(values).to_vec(),
+                Node::Compare { left, comparators } => {
+                    let mut children = vec![left];
+                    children.extend_from_slice(self.items(comparators));
+                    children
+                }
+                Node::BinOp { left, right, .. } => vec![left, right],
+                Node::UnaryOp { operand, .. } => vec![operand],
+                Node::Name(_) | Node::Number(_) | Node::True | Node::False | Node::Error => vec![],
+            };
+        }
+
+        /// Text of a node without its children: `lambda x y`, `or`, `==`, `+`, `x`, `42`.
+        pub fn label(&self, id: NodeId) -> String {
+            return match self.node(id) {
+                Node::Lambda { params, .. } => {
+                    let mut label = "lambda".to_string();
+                    for p in self.params(params) {
+                        label.push(' ');
+                        label.push_str(self.name(*p));
+                    }
+                    label
+                }
+                Node::BoolOp { op, .. } | Node::BinOp { op, .. } | Node::UnaryOp { op, .. } => op.to_string(),
+                Node::Compare { .. } => "==".to_string(),
+                Node::Name(s) | Node::Number(s) => self.name(s).to_string(),
+                Node::True => "True".to_string(),
+                Node::False => "False".to_string(),
+                Node::Error => "<error>".to_string(),
+            };
+        }
+
+        /// The subtree at `id` as an S-expression: `(lambda (x) (+ x 1))`.
+        pub fn sexpr(&self, id: NodeId) -> String {
+            enum Step {
+                Node(NodeId),
+                Text(&'static str),
+            }
+            let mut out = String::new();
+            let mut stack = vec![Step::Node(id)];
+            while let Some(step) = stack.pop() {
+                let id = match step {
+                    Step::Text(text) => {
+                        out.push_str(text);
+                        continue;
+                    }
+                    Step::Node(id) => id,
+                };
+                let children = self.children(id);
+                if children.is_empty() {
+                    out.push_str(&self.label(id));
+                    continue;
+                }
+                out.push('(');
+                match self.node(id) {
+                    Node::Lambda { params, .. } => {
+                        out.push_str("lambda (");
+                        let names: Vec<&str> = self.params(params).iter().map(|p| self.name(*p)).collect();
+                        out.push_str(&names.join(" "));
+                        out.push(')');
+                    }
+                    _ => out.push_str(&self.label(id)),
+                }
+                stack.push(Step::Text(")"));
+                for child in children.into_iter().rev() {
+                    stack.push(Step::Node(child));
+                    stack.push(Step::Text(" "));
+                }
+            }
+            return out;
+        }
+    }
+
+    /// Trees are equal when they have the same shape, names and spans, wherever their
+    /// nodes are in the arena: the tree `lower` gives equals the one `PrattParser` builds.
+    impl PartialEq for Ast {
+        fn eq(&self, other: &Ast) -> bool {
+            let mut stack = match (self.root, other.root) {
+                (Some(a), Some(b)) => vec![(a, b)],
+                (a, b) => return a.is_none() && b.is_none(),
+            };
+            while let Some((a, b)) = stack.pop() {
+                let same = mem::discriminant(&self.node(a)) == mem::discriminant(&other.node(b))
+                    && self.label(a) == other.label(b)
+                    && self.span(a) == other.span(b);
+                let children = (self.children(a), other.children(b));
+                if !same || children.0.len() != children.1.len() {
+                    return false;
+                }
+                stack.extend(children.0.into_iter().zip(children.1));
+            }
+            return true;
+        }
+    }
+
+    impl Eq for Ast {}
+
+    /// The whole tree as an S-expression, see `Ast::sexpr`.
+    impl fmt::Display for Ast {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            return match self.root {
+                Some(root) => f.write_str(&self.sexpr(root)),
+                None => f.write_str("<empty>"),
+            };
+        }
+    }
+}
@@ -0,0 +1,43 @@
+pub mod codes {
+    //! Stable codes of every lexer and parser diagnostic. A code is never reused
+    //! for another problem; its long explanation is shown by `--explain CODE`.
+
+    pub const UNEXPECTED_CHARACTER: &str = "LP0001";
+    pub const UNCLOSED_PARENTHESIS: &str = "LP0002";
+    pub const MISSING_COLON: &str = "LP0003";
+    pub const UNMATCHED_PARENTHESIS: &str = "LP0004";
+    pub const UNEXPECTED_TOKEN: &str = "LP0005";
+    pub const MISSING_LAMBDA: &str = "LP0006";
+    pub const INVALID_PARAMETERS: &str = "LP0007";
+    pub const FOREIGN_OPERATOR: &str = "LP0008";
+    pub const NOT_EQUAL: &str = "LP0009";
+    pub const ASSIGNMENT: &str = "LP0010";
+    pub const TRUE_DIVISION: &str = "LP0011";
+    pub const FOREIGN_CONSTANT: &str = "LP0012";
+    pub const TOO_DEEP: &str = "LP0013";
+    pub const LIMIT_EXCEEDED: &str = "LP0014";
+    pub const INVALID_NUMBER: &str = "LP0015";
+
+    const EXPLANATIONS: &[(&str, &str)] = &[
+        (UNEXPECTED_CHARACTER, include_str!("codes/LP0001.md")),
+        (UNCLOSED_PARENTHESIS, include_str!("codes/LP0002.md")),
+        (MISSING_COLON, include_str!("codes/LP0003.md")),
+        (UNMATCHED_PARENTHESIS, include_str!("codes/LP0004.md")),
+        (UNEXPECTED_TOKEN, include_str!("codes/LP0005.md")),
+        (MISSING_LAMBDA, include_str!("codes/LP0006.md")),
+        (INVALID_PARAMETERS, include_str!("codes/LP0007.md")),
+        (FOREIGN_OPERATOR, include_str!("codes/LP0008.md")),
+        (NOT_EQUAL, include_str!("codes/LP0009.md")),
+        (ASSIGNMENT, include_str!("codes/LP0010.md")),
+        (TRUE_DIVISION, include_str!("codes/LP0011.md")),
+        (FOREIGN_CONSTANT, include_str!("codes/LP0012.md")),
+        (TOO_DEEP, include_str!("codes/LP0013.md")),
+        (LIMIT_EXCEEDED, include_str!("codes/LP0014.md")),
+        (INVALID_NUMBER, include_str!("codes/LP0015.md")),
+    ];
+
+    /// Long explanation of `code`, with examples, in Markdown.
+    pub fn explain(code: &str) -> Option<&'static str> {
+        return EXPLANATIONS.iter().find(|(c, _)| c.eq_ignore_ascii_case(code)).map(|(_, text)| *text);
+    }
+}
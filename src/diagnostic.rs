@@ -0,0 +1,329 @@
+pub mod diagnostic {
+    use std::fmt;
+
+    use serde::Serialize;
+
+    use crate::lexer::lexer::{Span, TokenKind};
+
+    #[derive(Debug, Eq, PartialEq, Copy, Clone, Serialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum Severity {
+        Error,
+        Warning,
+    }
+
+    impl fmt::Display for Severity {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            return f.write_str(match self {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            });
+        }
+    }
+
+    /// A message attached to a span of the source.
+    #[derive(Debug, Eq, PartialEq, Clone, Serialize)]
+    pub struct Label {
+        pub span: Span,
+        pub message: String,
+    }
+
+    #[derive(Debug, Eq, PartialEq, Copy, Clone, Serialize)]
+    pub enum Applicability {
+        /// Applying the fix is known to give what the user meant.
+        MachineApplicable,
+        /// The fix gives valid code, which may not mean what the user meant.
+        MaybeIncorrect,
+    }
+
+    /// A fix: `span` of the source replaced by `replacement`.
+    #[derive(Debug, Eq, PartialEq, Clone, Serialize)]
+    pub struct Suggestion {
+        pub message: String,
+        pub span: Span,
+        pub replacement: String,
+        pub applicability: Applicability,
+    }
+
+    /// What the lexer, the parser and any later pass report about the source.
+    #[derive(Debug, Eq, PartialEq, Clone, Serialize)]
+    pub struct Diagnostic {
+        /// Stable identifier of the kind of problem, if it has one.
+        pub code: Option<&'static str>,
+        pub severity: Severity,
+        pub message: String,
+        /// Where the problem is; `label` is printed next to its underline.
+        pub span: Span,
+        pub label: String,
+        /// Related places, e.g. where an unclosed `(` was opened.
+        pub secondary: Vec<Label>,
+        pub notes: Vec<String>,
+        pub help: Vec<String>,
+        /// Tokens that would have been accepted at `span`, if the error is about a token.
+        pub expected: Vec<TokenKind>,
+        pub suggestions: Vec<Suggestion>,
+    }
+
+    impl Diagnostic {
+        pub fn error(message: String, span: Span) -> Diagnostic {
+            return Diagnostic::new(Severity::Error, message, span);
+        }
+
+        pub fn warning(message: String, span: Span) -> Diagnostic {
+            return Diagnostic::new(Severity::Warning, message, span);
+        }
+
+        fn new(severity: Severity, message: String, span: Span) -> Diagnostic {
+            return Diagnostic {
+                code: None,
+                severity,
+                message,
+                span,
+                label: String::new(),
+                secondary: Vec::new(),
+                notes: Vec::new(),
+                help: Vec::new(),
+                expected: Vec::new(),
+                suggestions: Vec::new(),
+            };
+        }
+
+        pub fn with_code(mut self, code: &'static str) -> Diagnostic {
+            self.code = Some(code);
+            return self;
+        }
+
+        pub fn with_label(mut self, label: &str) -> Diagnostic {
+            self.label = label.to_string();
+            return self;
+        }
+
+        pub fn with_secondary(mut self, span: Span, message: &str) -> Diagnostic {
+            self.secondary.push(Label { span, message: message.to_string() });
+            return self;
+        }
+
+        pub fn with_note(mut self, note: &str) -> Diagnostic {
+            self.notes.push(note.to_string());
+            return self;
+        }
+
+        pub fn with_help(mut self, help: &str) -> Diagnostic {
+            self.help.push(help.to_string());
+            return self;
+        }
+
+        pub fn with_expected(mut self, expected: Vec<TokenKind>) -> Diagnostic {
+            self.expected = expected;
+            return self;
+        }
+
+        pub fn with_suggestion(mut self, message: &str, span: Span, replacement: &str, applicability: Applicability) -> Diagnostic {
+            self.suggestions.push(Suggestion {
+                message: message.to_string(),
+                span,
+                replacement: replacement.to_string(),
+                applicability,
+            });
+            return self;
+        }
+
+        pub fn is_error(&self) -> bool {
+            return self.severity == Severity::Error;
+        }
+    }
+
+    /// 1-based line and column (in characters) of a byte offset. The end of input
+    /// right after a trailing newline is reported at the end of the last line.
+    pub fn line_col(source: &str, offset: usize) -> (usize, usize) {
+        let mut offset = offset.min(source.len());
+        if offset == source.len() && source.ends_with('\n') {
+            offset -= 1;
+        }
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        return (before.matches('\n').count() + 1, before[line_start..].chars().count() + 1);
+    }
+
+    /// A diagnostic along with the line and column its span starts and ends at.
+    #[derive(Serialize)]
+    struct Located<'a> {
+        #[serde(flatten)]
+        diagnostic: &'a Diagnostic,
+        line: usize,
+        column: usize,
+        end_line: usize,
+        end_column: usize,
+    }
+
+    /// The diagnostic as a single line of JSON.
+    pub fn to_json(d: &Diagnostic, source: &str) -> String {
+        let (line, column) = line_col(source, d.span.start);
+        let (end_line, end_column) = line_col(source, d.span.end);
+        let located = Located { diagnostic: d, line, column, end_line, end_column };
+        return serde_json::to_string(&located).unwrap();
+    }
+
+    const RESET: &str = "\x1b[0m";
+    const BOLD: &str = "\x1b[1m";
+    const RED: &str = "\x1b[1;31m";
+    const YELLOW: &str = "\x1b[1;33m";
+    const BLUE: &str = "\x1b[1;34m";
+
+    /// Prints diagnostics the way rustc does: the source line with the primary span
+    /// underlined by `^`, secondary spans by `-`, followed by notes and help.
+    pub struct Renderer<'a> {
+        pub source: &'a str,
+        /// Shown in the ` --> name:line:col` header.
+        pub name: &'a str,
+        pub color: bool,
+    }
+
+    struct Mark {
+        col: usize,
+        width: usize,
+        primary: bool,
+        message: String,
+    }
+
+    impl Renderer<'_> {
+        fn paint(&self, style: &str, text: &str) -> String {
+            if !self.color || text.is_empty() {
+                return text.to_string();
+            }
+            return format!("{}{}{}", style, text, RESET);
+        }
+
+        fn severity_style(&self, severity: Severity) -> &'static str {
+            return match severity {
+                Severity::Error => RED,
+                Severity::Warning => YELLOW,
+            };
+        }
+
+        pub fn render(&self, d: &Diagnostic) -> String {
+            let style = self.severity_style(d.severity);
+            let (line, col) = line_col(self.source, d.span.start);
+
+            // Every label, grouped by the line it starts on.
+            let mut lines: Vec<(usize, Vec<Mark>)> = Vec::new();
+            let primary = Label { span: d.span, message: d.label.clone() };
+            for (label, is_primary) in std::iter::once((&primary, true)).chain(d.secondary.iter().map(|l| (l, false))) {
+                let (l, c) = line_col(self.source, label.span.start);
+                let text = self.source.get(label.span.start..label.span.end).unwrap_or("");
+                let text = text.split('\n').next().unwrap_or("");
+                let mark = Mark { col: c, width: text.chars().count().max(1), primary: is_primary, message: label.message.clone() };
+                match lines.iter_mut().find(|(n, _)| *n == l) {
+                    Some((_, marks)) => marks.push(mark),
+                    None => lines.push((l, vec![mark])),
+                }
+            }
+            lines.sort_by_key(|(n, _)| *n);
+
+            let gutter = lines.last().map_or(1, |(n, _)| n.to_string().len());
+            let pad = " ".repeat(gutter);
+            let bar = self.paint(BLUE, "|");
+
+            let severity = match d.code {
+                Some(code) => format!("{}[{}]", d.severity, code),
+                None => d.severity.to_string(),
+            };
+            let mut out = format!("{}{}\n", self.paint(style, &severity), self.paint(BOLD, &format!(": {}", d.message)));
+            out += &format!("{}{} {}:{}:{}\n", pad, self.paint(BLUE, "-->"), self.name, line, col);
+            out += &format!("{} {}\n", pad, bar);
+
+            let source_lines: Vec<&str> = self.source.split('\n').collect();
+            let mut prev = None;
+            for (n, mut marks) in lines {
+                if prev.is_some_and(|p| n > p + 1) {
+                    out += &format!("{}\n", self.paint(BLUE, "..."));
+                }
+                prev = Some(n);
+                marks.sort_by_key(|m| m.col);
+                let text = source_lines.get(n - 1).map_or("", |l| l.trim_end_matches('\r'));
+                out += &format!("{} {} {}\n", self.paint(BLUE, &format!("{:>w$}", n, w = gutter)), bar, text);
+                out += &self.render_marks(&pad, &bar, style, &marks);
+            }
+
+            if !d.notes.is_empty() || !d.help.is_empty() {
+                out += &format!("{} {}\n", pad, bar);
+            }
+            for note in &d.notes {
+                out += &format!("{} {} {}\n", pad, self.paint(BLUE, "="), self.paint(BOLD, "note: ") + note);
+            }
+            for help in &d.help {
+                out += &format!("{} {} {}\n", pad, self.paint(BLUE, "="), self.paint(BOLD, "help: ") + help);
+            }
+            for suggestion in &d.suggestions {
+                out += &self.render_suggestion(suggestion);
+            }
+            return out;
+        }
+
+        /// The line of the suggestion with the fix applied and `~` under the replacement.
+        fn render_suggestion(&self, s: &Suggestion) -> String {
+            let (line, col) = line_col(self.source, s.span.start);
+            let text = self.source.split('\n').nth(line - 1).unwrap_or("").trim_end_matches('\r');
+            let start = s.span.start - (self.source[..s.span.start].rfind('\n').map_or(0, |i| i + 1));
+            let end = (start + s.span.end - s.span.start).min(text.len());
+            let patched = format!("{}{}{}", &text[..start], s.replacement, &text[end..]);
+
+            let pad = " ".repeat(line.to_string().len());
+            let bar = self.paint(BLUE, "|");
+            let mut out = format!("{}\n", self.paint(BOLD, "help") + &format!(": {}", s.message));
+            out += &format!("{} {}\n", pad, bar);
+            out += &format!("{} {} {}\n", self.paint(BLUE, &line.to_string()), bar, patched);
+            let underline = "~".repeat(s.replacement.chars().count().max(1));
+            out += &format!("{} {} {}{}\n", pad, bar, " ".repeat(col - 1), self.paint(BLUE, &underline));
+            return out;
+        }
+
+        /// Underlines of one source line. The rightmost label goes right after its
+        /// underline, the others hang below on `|` connectors, rightmost first.
+        fn render_marks(&self, pad: &str, bar: &str, style: &str, marks: &[Mark]) -> String {
+            let mark_style = |m: &Mark| if m.primary { style } else { BLUE };
+
+            let mut row = String::new();
+            let mut width = 0;
+            for m in marks {
+                let start = (m.col - 1).max(width);
+                let end = m.col - 1 + m.width;
+                if end <= start {
+                    continue;
+                }
+                row += &" ".repeat(start - width);
+                row += &self.paint(mark_style(m), &(if m.primary { "^" } else { "-" }).repeat(end - start));
+                width = end;
+            }
+            let (last, hanging) = marks.split_last().unwrap();
+            if !last.message.is_empty() {
+                row += " ";
+                row += &self.paint(mark_style(last), &last.message);
+            }
+            let mut out = format!("{} {} {}\n", pad, bar, row);
+
+            let hanging: Vec<&Mark> = hanging.iter().filter(|m| !m.message.is_empty()).collect();
+            if hanging.is_empty() {
+                return out;
+            }
+            let connectors = |count: usize| {
+                let mut row = String::new();
+                let mut width = 0;
+                for m in &hanging[..count] {
+                    row += &" ".repeat((m.col - 1).saturating_sub(width));
+                    row += &self.paint(mark_style(m), "|");
+                    width = m.col;
+                }
+                return (row, width);
+            };
+            out += &format!("{} {} {}\n", pad, bar, connectors(hanging.len()).0);
+            for i in (0..hanging.len()).rev() {
+                let (mut row, width) = connectors(i);
+                row += &" ".repeat((hanging[i].col - 1).saturating_sub(width));
+                row += &self.paint(mark_style(hanging[i]), &hanging[i].message);
+                out += &format!("{} {} {}\n", pad, bar, row);
+            }
+            return out;
+        }
+    }
+}
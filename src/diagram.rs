@@ -0,0 +1,159 @@
+pub mod diagram {
+    //! The derivation tree or the AST as a Mermaid flowchart or a PlantUML work
+    //! breakdown structure, for Markdown documents. Node kinds are coloured as in `to_dot`.
+
+    use std::fmt::Write;
+
+    use petgraph::graph::NodeIndex;
+
+    use crate::ast::ast::{Ast, Node};
+    use crate::graphviz::graphviz::NodeKind;
+    use crate::parser::parser::{Parser, Tree};
+
+    /// Nodes of a tree in depth-first order, each with its depth and parent.
+    #[derive(Debug, Eq, PartialEq, Clone, Default)]
+    pub struct Diagram {
+        pub nodes: Vec<DiagramNode>,
+    }
+
+    #[derive(Debug, Eq, PartialEq, Clone)]
+    pub struct DiagramNode {
+        pub label: String,
+        pub kind: NodeKind,
+        pub depth: usize,
+        /// Index in `Diagram::nodes`; `None` for the root.
+        pub parent: Option<usize>,
+    }
+
+    fn class(kind: NodeKind) -> &'static str {
+        return match kind {
+            NodeKind::NonTerminal => "nonterminal",
+            NodeKind::Terminal => "terminal",
+            NodeKind::Epsilon => "epsilon",
+            NodeKind::Error => "error",
+        };
+    }
+
+    /// A Mermaid label: anything but letters, digits and spaces becomes an entity
+    /// code, so that `//`, `==`, `"` or `ε` are never read as syntax.
+    fn mermaid_label(label: &str) -> String {
+        let mut out = String::new();
+        for c in label.chars() {
+            if c.is_ascii_alphanumeric() || c == ' ' {
+                out.push(c);
+            } else {
+                write!(out, "#{};", c as u32).unwrap();
+            }
+        }
+        return out;
+    }
+
+    /// Characters that start creole markup, links, HTML tags or entities in PlantUML.
+    const CREOLE: &str = "*/\"-_~=<>[]&";
+
+    /// A PlantUML label: creole markup such as `//` (italics) or `==` is escaped with `~`,
+    /// non-ASCII characters are written `<U+03B5>`.
+    fn plantuml_label(label: &str) -> String {
+        let mut out = String::new();
+        for c in label.chars() {
+            if !c.is_ascii() {
+                write!(out, "<U+{:04X}>", c as u32).unwrap();
+            } else if CREOLE.contains(c) {
+                out.push('~');
+                out.push(c);
+            } else {
+                out.push(c);
+            }
+        }
+        return out;
+    }
+
+    impl Diagram {
+        /// The nodes below `root`, depth first.
+        fn walk<N: Copy>(root: N, node: impl Fn(N) -> (String, NodeKind), children: impl Fn(N) -> Vec<N>) -> Diagram {
+            let mut nodes = Vec::new();
+            let mut stack = vec![(root, 0, None)];
+            while let Some((id, depth, parent)) = stack.pop() {
+                let (label, kind) = node(id);
+                let index = nodes.len();
+                nodes.push(DiagramNode { label, kind, depth, parent });
+                stack.extend(children(id).into_iter().rev().map(|child| (child, depth + 1, Some(index))));
+            }
+            return Diagram { nodes };
+        }
+
+        /// The derivation tree below `root`, without its ε leaves if `hide_epsilon`.
+        pub fn derivation(parser: &Parser, root: NodeIndex, hide_epsilon: bool) -> Diagram {
+            let node = |id: NodeIndex| (parser.graph[id].clone(), NodeKind::of(parser, id));
+            let children = |id: NodeIndex| -> Vec<NodeIndex> {
+                return Tree::children(&parser.graph, id)
+                    .into_iter()
+                    .filter(|child| !hide_epsilon || NodeKind::of(parser, *child) != NodeKind::Epsilon)
+                    .collect();
+            };
+            return Diagram::walk(root, node, children);
+        }
+
+        /// The AST from its root; empty if it has none.
+        pub fn ast(ast: &Ast) -> Diagram {
+            let Some(root) = ast.root() else {
+                return Diagram::default();
+            };
+            let node = |id| {
+                let kind = match ast.node(id) {
+                    Node::Error => NodeKind::Error,
+                    Node::Name(_) | Node::Number(_) | Node::True | Node::False => NodeKind::Terminal,
+                    _ => NodeKind::NonTerminal,
+                };
+                (ast.label(id), kind)
+            };
+            return Diagram::walk(root, node, |id| ast.children(id));
+        }
+
+        /// A Mermaid `graph TD`: rounded non-terminals, boxed terminals, hexagonal errors.
+        pub fn mermaid(&self) -> String {
+            let mut out = "graph TD\n".to_string();
+            for (i, node) in self.nodes.iter().enumerate() {
+                let label = mermaid_label(&node.label);
+                let shape = match node.kind {
+                    NodeKind::NonTerminal => format!("(\"{}\")", label),
+                    NodeKind::Terminal | NodeKind::Epsilon => format!("[\"{}\"]", label),
+                    NodeKind::Error => format!("{{{{\"{}\"}}}}", label),
+                };
+                writeln!(out, "    n{}{}:::{}", i, shape, class(node.kind)).unwrap();
+            }
+            for (i, node) in self.nodes.iter().enumerate() {
+                if let Some(parent) = node.parent {
+                    writeln!(out, "    n{} --> n{}", parent, i).unwrap();
+                }
+            }
+            for kind in [NodeKind::NonTerminal, NodeKind::Terminal, NodeKind::Epsilon, NodeKind::Error] {
+                let (fill, stroke) = kind.colors();
+                writeln!(out, "    classDef {} fill:{},stroke:{}", class(kind), fill, stroke).unwrap();
+            }
+            return out;
+        }
+
+        /// A PlantUML work breakdown structure: one line per node, `*` repeated by depth.
+        /// With a `max_depth`, the children of the nodes at that depth are one `…` node,
+        /// as in `render_ast`; without, every node is kept.
+        pub fn plantuml(&self, max_depth: Option<usize>) -> String {
+            let max_depth = max_depth.unwrap_or(usize::MAX);
+            let mut out = "@startwbs\n".to_string();
+            for (i, node) in self.nodes.iter().enumerate() {
+                // Depth first, the first child comes right after its parent.
+                let (kind, label) = if node.depth <= max_depth {
+                    (node.kind, node.label.as_str())
+                } else if node.depth - 1 == max_depth && node.parent == i.checked_sub(1) {
+                    (NodeKind::Epsilon, "…")
+                } else {
+                    continue;
+                };
+                let (fill, _) = kind.colors();
+                writeln!(out, "{}[{}] {}", "*".repeat(node.depth + 1), fill, plantuml_label(label)).unwrap();
+            }
+            out.push_str("@endwbs\n");
+            return out;
+        }
+    }
+}
@@ -0,0 +1,245 @@
+pub mod formatter {
+    use std::fmt;
+
+    use crate::diagnostic::diagnostic::Diagnostic;
+    use crate::limits::limits::LimitExceeded;
+    use crate::parser::parser::Parser;
+    use crate::unparser::unparser::{leaves, render, Leaf, Role};
+
+    pub const DEFAULT_LINE_LENGTH: usize = 88;
+    const INDENT: &str = "    ";
+
+    #[derive(Debug, Eq, PartialEq, Clone)]
+    pub enum FormatError {
+        /// The source does not parse; the first syntax error is kept.
+        Syntax(Box<Diagnostic>),
+        /// The source is nested too deeply to be parsed.
+        Limit(LimitExceeded),
+        /// Formatted code does not parse back to the same tokens.
+        NotEquivalent(String),
+        /// Formatting the formatted code once more changes it.
+        Unstable(String),
+    }
+
+    impl fmt::Display for FormatError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            return match self {
+                FormatError::Syntax(e) => write!(f, "{} at {}..{}", e.message, e.span.start, e.span.end),
+                FormatError::Limit(limit) => write!(f, "{}", limit),
+                FormatError::NotEquivalent(src) => write!(f, "Formatted code is not equivalent to the source: {}", src),
+                FormatError::Unstable(src) => write!(f, "Formatting is not stable for: {}", src),
+            };
+        }
+    }
+
+    struct Line {
+        indent: usize,
+        leaves: Vec<Leaf>,
+        inside_brackets: bool,
+    }
+
+    impl Line {
+        fn render(&self) -> String {
+            return INDENT.repeat(self.indent) + render(&self.leaves).as_str();
+        }
+
+        /// Bracket depth of every leaf; brackets themselves sit at the outer depth.
+        /// Head and tail lines of a split may hold unmatched brackets.
+        fn depths(&self) -> Vec<usize> {
+            let mut depth = 0usize;
+            let mut result = Vec::with_capacity(self.leaves.len());
+            for leaf in &self.leaves {
+                if leaf.role == Role::Close {
+                    depth = depth.saturating_sub(1);
+                }
+                result.push(depth);
+                if leaf.role == Role::Open {
+                    depth += 1;
+                }
+            }
+            return result;
+        }
+
+        fn part(&self, leaves: &[Leaf], indent: usize, inside_brackets: bool) -> Line {
+            return Line { indent, leaves: leaves.to_vec(), inside_brackets };
+        }
+    }
+
+    fn parse_leaves(src: &str) -> Result<Vec<Leaf>, FormatError> {
+        let mut parser = Parser::get(src);
+        let root = parser.parse();
+        if let Some(limit) = parser.exceeded() {
+            return Err(FormatError::Limit(limit));
+        }
+        return match parser.diagnostics.into_iter().find(|d| d.is_error()) {
+            None => Ok(leaves(&parser.graph, root)),
+            Some(e) => Err(FormatError::Syntax(Box::new(e))),
+        };
+    }
+
+    /// Formats a single lambda, wrapping it at `line_length` columns.
+    ///
+    /// The result never ends with a newline. Output is checked to parse back to the
+    /// same tokens and to be left untouched by a second formatting pass.
+    pub fn format_lambda(src: &str, line_length: usize) -> Result<String, FormatError> {
+        let source_leaves = parse_leaves(src)?;
+        let result = format_leaves(source_leaves.clone(), line_length);
+
+        if parse_leaves(&result)? != source_leaves {
+            return Err(FormatError::NotEquivalent(src.to_string()));
+        }
+        if format_leaves(parse_leaves(&result)?, line_length) != result {
+            return Err(FormatError::Unstable(src.to_string()));
+        }
+        return Ok(result);
+    }
+
+    /// Formats a file with one lambda per logical line.
+    ///
+    /// A newline inside parentheses continues the current lambda, just like in Python.
+    /// Runs of blank lines are kept but collapsed to at most two.
+    pub fn format_source(src: &str, line_length: usize) -> Result<String, FormatError> {
+        let mut result = String::new();
+        let mut blank_lines = 0;
+        for line in logical_lines(src) {
+            if line.trim().is_empty() {
+                blank_lines += 1;
+                continue;
+            }
+            if !result.is_empty() {
+                result.push_str(&"\n".repeat(blank_lines.min(2)));
+            }
+            blank_lines = 0;
+            result.push_str(&format_lambda(&line, line_length)?);
+            result.push('\n');
+        }
+        return Ok(result);
+    }
+
+    fn logical_lines(src: &str) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut cur = String::new();
+        let mut depth = 0usize;
+        for c in src.chars() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                '\n' if depth == 0 => {
+                    lines.push(std::mem::take(&mut cur));
+                    continue;
+                }
+                _ => {}
+            }
+            cur.push(c);
+        }
+        if !cur.trim().is_empty() {
+            lines.push(cur);
+        }
+        return lines;
+    }
+
+    fn format_leaves(leaves: Vec<Leaf>, line_length: usize) -> String {
+        let mut out = Vec::new();
+        transform(Line { indent: 0, leaves, inside_brackets: false }, line_length, &mut out);
+        return out.join("\n");
+    }
+
+    /// Black's line splitting: a line that does not fit is first split on its
+    /// lowest-precedence operators (only inside brackets), otherwise on its last
+    /// bracket pair. Every resulting line is processed again.
+    fn transform(line: Line, line_length: usize, out: &mut Vec<String>) {
+        let text = line.render();
+        if text.chars().count() <= line_length {
+            out.push(text);
+            return;
+        }
+
+        let mut split = None;
+        if line.inside_brackets {
+            split = delimiter_split(&line);
+        }
+        if split.is_none() {
+            split = right_hand_split(&line, line_length);
+        }
+
+        match split {
+            Some(lines) => {
+                for part in lines {
+                    transform(part, line_length, out);
+                }
+            }
+            None => out.push(text),
+        }
+    }
+
+    fn delimiter_split(line: &Line) -> Option<Vec<Line>> {
+        let depths = line.depths();
+        // A line may start with the operator it was split before; that is no split point.
+        let priority = |i: usize| match line.leaves[i].role {
+            Role::Binary(p) if depths[i] == 0 && i > 0 => p,
+            _ => 0,
+        };
+
+        let max_priority = (0..line.leaves.len()).map(priority).max().unwrap_or(0);
+        if max_priority == 0 {
+            return None;
+        }
+
+        let mut result = Vec::new();
+        let mut start = 0;
+        for i in 1..line.leaves.len() {
+            if priority(i) == max_priority {
+                result.push(line.part(&line.leaves[start..i], line.indent, true));
+                start = i;
+            }
+        }
+        result.push(line.part(&line.leaves[start..], line.indent, true));
+        return Some(result);
+    }
+
+    /// Splits around the last bracket pair whose head still fits, falling back to
+    /// the very last pair, like Black's right hand split with omitted trailers.
+    fn right_hand_split(line: &Line, line_length: usize) -> Option<Vec<Line>> {
+        let depths = line.depths();
+        let mut opening = vec![0; line.leaves.len()];
+        let mut stack = Vec::new();
+        for (i, leaf) in line.leaves.iter().enumerate() {
+            match leaf.role {
+                Role::Open => stack.push(i),
+                Role::Close => opening[i] = stack.pop()?,
+                _ => {}
+            }
+        }
+
+        // The last pair is always a candidate, earlier ones only while the part of
+        // the line after them would still fit.
+        let mut pairs = Vec::new();
+        let mut tail_length = INDENT.len() * line.indent;
+        for i in (0..line.leaves.len()).rev() {
+            tail_length += line.leaves[i].text.chars().count() + 1;
+            if !pairs.is_empty() && tail_length > line_length {
+                break;
+            }
+            if depths[i] == 0 && line.leaves[i].role == Role::Close && opening[i] + 1 < i {
+                pairs.push((opening[i], i));
+            }
+        }
+
+        let split_at = |(open, close): (usize, usize)| {
+            return vec![
+                line.part(&line.leaves[..=open], line.indent, false),
+                line.part(&line.leaves[open + 1..close], line.indent + 1, true),
+                line.part(&line.leaves[close..], line.indent, false),
+            ];
+        };
+
+        let first = *pairs.first()?;
+        for pair in pairs {
+            let lines = split_at(pair);
+            if lines[0].render().chars().count() <= line_length {
+                return Some(lines);
+            }
+        }
+        return Some(split_at(first));
+    }
+}
@@ -0,0 +1,261 @@
+pub mod generator {
+    //! Random lambdas for stress tests: `Generator::lambda` walks `PRODUCTIONS` and
+    //! always yields an input the parsers accept, `Generator::mutant` breaks one with a
+    //! few token-level edits to reach the error paths.
+
+    use crate::grammar::grammar::{NonTerminal, Production, Symbol, PRODUCTIONS};
+    use crate::lexer::lexer::{Lexer, Span, TokenKind};
+    use crate::parser::parser::Parser;
+
+    use NonTerminal as NT;
+    use Symbol::{N, T};
+
+    /// What `Generator` produces.
+    #[derive(Debug, Eq, PartialEq, Clone)]
+    pub struct GeneratorOptions {
+        /// Seed of the pseudo-random sequence; the same seed gives the same inputs.
+        pub seed: u64,
+        /// Deepest derivation tree. Past it only the productions that end soonest are taken.
+        pub max_depth: usize,
+        /// Relative weight of every production of `PRODUCTIONS`, in the same order.
+        pub weights: Vec<u32>,
+        /// Names used for parameters and variables; keywords are not names, see `is_identifier`.
+        pub identifiers: Vec<String>,
+    }
+
+    /// Deep enough for a few levels of parentheses and nested lambdas.
+    pub const DEFAULT_MAX_DEPTH: usize = 40;
+
+    impl Default for GeneratorOptions {
+        fn default() -> GeneratorOptions {
+            return GeneratorOptions {
+                seed: 0x5eed,
+                max_depth: DEFAULT_MAX_DEPTH,
+                weights: PRODUCTIONS.iter().map(default_weight).collect(),
+                identifiers: ["x", "y", "z", "foo", "bar_1"].iter().map(|s| s.to_string()).collect(),
+            };
+        }
+    }
+
+    /// Operator chains end more often than they go on, and plain operands are more
+    /// common than signs, `not`, parentheses and nested lambdas.
+    fn default_weight(p: &Production) -> u32 {
+        return match (p.lhs, p.rhs.first()) {
+            (NT::V, Some(_)) => 3,
+            (NT::V, None) => 1,
+            (_, None) => 6,
+            (NT::Sp, Some(N(NT::E))) => 6,
+            (NT::A, Some(N(_))) => 6,
+            (NT::F, Some(N(_))) => 8,
+            (NT::C, Some(T(TokenKind::Variable))) => 4,
+            (NT::C, Some(T(TokenKind::Number))) => 3,
+            _ => 1,
+        };
+    }
+
+    /// Tokens `mutant` inserts or substitutes: the terminals of the grammar and a few
+    /// near misses from other languages.
+    const NOISE: &[&str] = &[
+        "lambda", ":", ",", "(", ")", "or", "and", "not", "==", "+", "-", "*", "//", "%",
+        "True", "False", "x", "0", "&&", "||", "!", "!=", "=", "/", "**", "->", "true", "null", "$",
+    ];
+
+    /// Edits tried before `mutant` falls back to a `)` that closes nothing.
+    const MUTATION_ATTEMPTS: usize = 100;
+
+    /// xorshift64*: small, fast and reproducible, which is all a test generator needs.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            return self.0.wrapping_mul(0x2545_f491_4f6c_dd1d);
+        }
+
+        /// Uniform in `0..n`, `n > 0`.
+        fn below(&mut self, n: usize) -> usize {
+            return (self.next() % n as u64) as usize;
+        }
+    }
+
+    pub struct Generator {
+        options: GeneratorOptions,
+        rng: Rng,
+        /// Height of the lowest derivation tree of every `NonTerminal`.
+        heights: Vec<usize>,
+    }
+
+    impl Generator {
+        pub fn new(options: GeneratorOptions) -> Generator {
+            assert_eq!(options.weights.len(), PRODUCTIONS.len(), "one weight per production");
+            assert!(!options.identifiers.is_empty(), "the identifier pool is empty");
+            for name in &options.identifiers {
+                assert!(is_identifier(name), "`{}` is not an identifier", name);
+            }
+            // xorshift never leaves 0.
+            let rng = Rng(options.seed.max(1));
+            return Generator { options, rng, heights: heights() };
+        }
+
+        /// A syntactically valid lambda, as tokens.
+        pub fn tokens(&mut self) -> Vec<String> {
+            let mut tokens = Vec::new();
+            let mut stack = vec![(N(NT::S), 1)];
+            while let Some((symbol, depth)) = stack.pop() {
+                match symbol {
+                    T(kind) => tokens.push(self.text(kind)),
+                    N(nt) => {
+                        let p = self.choose(nt, depth);
+                        stack.extend(p.rhs.iter().rev().map(|s| (*s, depth + 1)));
+                    }
+                }
+            }
+            return tokens;
+        }
+
+        /// A syntactically valid lambda.
+        pub fn lambda(&mut self) -> String {
+            let tokens = self.tokens();
+            return render(&tokens);
+        }
+
+        /// A lambda with one to three tokens inserted, deleted, duplicated, swapped or
+        /// replaced, such that the parser reports an error.
+        pub fn mutant(&mut self) -> String {
+            for _ in 0..MUTATION_ATTEMPTS {
+                let mut tokens = self.tokens();
+                for _ in 0..1 + self.rng.below(3) {
+                    self.mutate(&mut tokens);
+                }
+                let src = render(&tokens);
+                let mut parser = Parser::get(&src);
+                parser.parse();
+                if parser.diagnostics.iter().any(|d| d.is_error()) {
+                    return src;
+                }
+            }
+            // Nothing may follow a whole lambda.
+            let mut tokens = self.tokens();
+            tokens.push(")".to_string());
+            return render(&tokens);
+        }
+
+        fn mutate(&mut self, tokens: &mut Vec<String>) {
+            let i = self.rng.below(tokens.len());
+            let noise = NOISE[self.rng.below(NOISE.len())].to_string();
+            match self.rng.below(5) {
+                0 if tokens.len() > 1 => {
+                    tokens.remove(i);
+                }
+                1 => tokens.insert(i, tokens[i].clone()),
+                2 if i + 1 < tokens.len() => tokens.swap(i, i + 1),
+                3 => tokens[i] = noise,
+                _ => tokens.insert(i + self.rng.below(2), noise),
+            }
+        }
+
+        /// A production of `nt` by weight, among those that fit under `max_depth`; the
+        /// lowest ones once none does.
+        fn choose(&mut self, nt: NonTerminal, depth: usize) -> &'static Production {
+            let candidates: Vec<(&'static Production, usize)> = PRODUCTIONS
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| p.lhs == nt)
+                .map(|(i, p)| (p, i))
+                .collect();
+            let fits: Vec<_> = candidates
+                .iter()
+                .filter(|(p, _)| depth + height(p, &self.heights) <= self.options.max_depth)
+                .copied()
+                .collect();
+            let fits = if fits.is_empty() {
+                let lowest = candidates.iter().map(|(p, _)| height(p, &self.heights)).min();
+                candidates.into_iter().filter(|(p, _)| Some(height(p, &self.heights)) == lowest).collect()
+            } else {
+                fits
+            };
+
+            let total: u64 = fits.iter().map(|(_, i)| self.options.weights[*i] as u64).sum();
+            if total == 0 {
+                return fits[self.rng.below(fits.len())].0;
+            }
+            let mut pick = self.rng.next() % total;
+            for (p, i) in &fits {
+                let weight = self.options.weights[*i] as u64;
+                if pick < weight {
+                    return p;
+                }
+                pick -= weight;
+            }
+            unreachable!("the pick is below the total weight");
+        }
+
+        fn text(&mut self, kind: TokenKind) -> String {
+            return match kind {
+                TokenKind::Variable => {
+                    let identifiers = &self.options.identifiers;
+                    identifiers[self.rng.below(identifiers.len())].clone()
+                }
+                TokenKind::Number => match self.rng.below(4) {
+                    0 => "0".to_string(),
+                    1 => self.rng.below(10).to_string(),
+                    _ => self.rng.below(100_000).to_string(),
+                },
+                _ => kind.to_string().trim_matches('`').to_string(),
+            };
+        }
+    }
+
+    /// Whether `name` lexes as a single identifier: `x_1` does, `lambda`, `True`, `1x`
+    /// and `a b` do not.
+    pub fn is_identifier(name: &str) -> bool {
+        let items: Vec<_> = Lexer::get(name).collect();
+        return match items.as_slice() {
+            [Ok(token)] => token.token.kind() == TokenKind::Variable && token.span == Span { start: 0, end: name.len() },
+            _ => false,
+        };
+    }
+
+    /// Height of the derivation tree below `p`, each non-terminal taking its lowest.
+    fn height(p: &Production, heights: &[usize]) -> usize {
+        let below = p.rhs.iter().map(|s| match s {
+            T(_) => 0,
+            N(nt) => heights[*nt as usize],
+        });
+        return 1 + below.max().unwrap_or(0);
+    }
+
+    /// Lowest derivation tree of every `NonTerminal`, by fixed point.
+    fn heights() -> Vec<usize> {
+        let mut heights = vec![usize::MAX / 2; NT::ALL.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for p in PRODUCTIONS {
+                let h = height(p, &heights);
+                if h < heights[p.lhs as usize] {
+                    heights[p.lhs as usize] = h;
+                    changed = true;
+                }
+            }
+        }
+        return heights;
+    }
+
+    /// Joins tokens with single spaces, none inside parentheses or before `,` and `:`.
+    pub fn render(tokens: &[String]) -> String {
+        let mut out = String::new();
+        for (i, token) in tokens.iter().enumerate() {
+            let glued = i == 0
+                || tokens[i - 1] == "("
+                || matches!(token.as_str(), ")" | "," | ":");
+            if !glued {
+                out.push(' ');
+            }
+            out.push_str(token);
+        }
+        return out;
+    }
+}
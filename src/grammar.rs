@@ -0,0 +1,407 @@
+pub mod grammar {
+    //! The grammar of README.md as data. Nullable, FIRST and FOLLOW sets and the LL(1)
+    //! prediction table are computed from it once, instead of being written by hand
+    //! into every parser method; `conflicts` tells whether it is still LL(1) and
+    //! `markdown` prints the table README.md shows.
+
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::sync::OnceLock;
+
+    use crate::lexer::lexer::Operations::{Add, And, Div, Eq, Mod, Mul, Not, Or, Sub};
+    use crate::lexer::lexer::TokenKind;
+
+    #[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+    pub enum NonTerminal {
+        S, V, Vp, Sp, E, Ep, O, Op, A, N, Np, Q, Qp, T, Tp, F, C,
+    }
+
+    use NonTerminal as NT;
+
+    /// A grammar symbol: a token or a non-terminal.
+    #[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+    pub enum Symbol {
+        T(TokenKind),
+        N(NonTerminal),
+    }
+
+    use Symbol::{N, T};
+
+    /// `lhs → rhs`; an empty `rhs` is the ε-production.
+    #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+    pub struct Production {
+        pub lhs: NonTerminal,
+        pub rhs: &'static [Symbol],
+    }
+
+    const fn p(lhs: NonTerminal, rhs: &'static [Symbol]) -> Production {
+        return Production { lhs, rhs };
+    }
+
+    /// Productions of every non-terminal, in the order README.md lists them. `S` is the
+    /// start symbol.
+    pub const PRODUCTIONS: &[Production] = &[
+        p(NT::S, &[T(TokenKind::Lambda), N(NT::V), T(TokenKind::Colon), N(NT::Sp)]),
+        p(NT::V, &[T(TokenKind::Variable), N(NT::Vp)]),
+        p(NT::V, &[]),
+        p(NT::Vp, &[T(TokenKind::Comma), N(NT::V)]),
+        p(NT::Vp, &[]),
+        p(NT::Sp, &[N(NT::S)]),
+        p(NT::Sp, &[N(NT::E)]),
+        p(NT::E, &[N(NT::O), N(NT::Ep)]),
+        p(NT::Ep, &[T(TokenKind::Op(Or)), N(NT::E)]),
+        p(NT::Ep, &[]),
+        p(NT::O, &[N(NT::A), N(NT::Op)]),
+        p(NT::Op, &[T(TokenKind::Op(And)), N(NT::O)]),
+        p(NT::Op, &[]),
+        p(NT::A, &[T(TokenKind::Op(Not)), N(NT::N)]),
+        p(NT::A, &[N(NT::N)]),
+        p(NT::N, &[N(NT::Q), N(NT::Np)]),
+        p(NT::Np, &[T(TokenKind::Op(Eq)), N(NT::N)]),
+        p(NT::Np, &[]),
+        p(NT::Q, &[N(NT::T), N(NT::Qp)]),
+        p(NT::Qp, &[T(TokenKind::Op(Add)), N(NT::Q)]),
+        p(NT::Qp, &[T(TokenKind::Op(Sub)), N(NT::Q)]),
+        p(NT::Qp, &[]),
+        p(NT::T, &[N(NT::F), N(NT::Tp)]),
+        p(NT::Tp, &[T(TokenKind::Op(Mul)), N(NT::T)]),
+        p(NT::Tp, &[T(TokenKind::Op(Div)), N(NT::T)]),
+        p(NT::Tp, &[T(TokenKind::Op(Mod)), N(NT::T)]),
+        p(NT::Tp, &[]),
+        p(NT::F, &[T(TokenKind::Op(Add)), N(NT::C)]),
+        p(NT::F, &[T(TokenKind::Op(Sub)), N(NT::C)]),
+        p(NT::F, &[N(NT::C)]),
+        p(NT::C, &[T(TokenKind::Variable)]),
+        p(NT::C, &[T(TokenKind::Number)]),
+        p(NT::C, &[T(TokenKind::True)]),
+        p(NT::C, &[T(TokenKind::False)]),
+        p(NT::C, &[T(TokenKind::LParen), N(NT::Sp), T(TokenKind::RParen)]),
+    ];
+
+    impl NonTerminal {
+        pub const ALL: [NonTerminal; 17] = [
+            NT::S, NT::V, NT::Vp, NT::Sp, NT::E, NT::Ep, NT::O, NT::Op, NT::A,
+            NT::N, NT::Np, NT::Q, NT::Qp, NT::T, NT::Tp, NT::F, NT::C,
+        ];
+
+        /// FIRST set without ε.
+        pub fn first(self) -> &'static [TokenKind] {
+            return &sets().first[self as usize];
+        }
+
+        /// Whether ε is in the FIRST set.
+        pub fn nullable(self) -> bool {
+            return sets().nullable[self as usize];
+        }
+
+        pub fn follow(self) -> &'static [TokenKind] {
+            return &sets().follow[self as usize];
+        }
+
+        /// Name in README.md: `E'` where the tree says `Ep`.
+        pub fn name(self) -> &'static str {
+            return match self {
+                NT::Ep => "E'",
+                NT::Op => "O'",
+                NT::Np => "N'",
+                NT::Qp => "Q'",
+                NT::Tp => "T'",
+                _ => self.label(),
+            };
+        }
+
+        /// Label of the node deriving this non-terminal in the tree.
+        pub fn label(self) -> &'static str {
+            return match self {
+                NT::S => "S",
+                NT::V => "V",
+                NT::Vp => "V'",
+                NT::Sp => "S'",
+                NT::E => "E",
+                NT::Ep => "Ep",
+                NT::O => "O",
+                NT::Op => "Op",
+                NT::A => "A",
+                NT::N => "N",
+                NT::Np => "Np",
+                NT::Q => "Q",
+                NT::Qp => "Qp",
+                NT::T => "T",
+                NT::Tp => "Tp",
+                NT::F => "F",
+                NT::C => "C",
+            };
+        }
+
+        /// Every token some production of this non-terminal is predicted on.
+        pub fn expected(self) -> Vec<TokenKind> {
+            let mut expected = self.first().to_vec();
+            if self.nullable() {
+                expected.extend_from_slice(self.follow());
+            }
+            return expected;
+        }
+    }
+
+    /// A terminal as README.md writes it: `\String`, `\Num`, `EOL`, `` `,` `` and the
+    /// text of the others.
+    fn terminal(kind: TokenKind) -> String {
+        return match kind {
+            TokenKind::Variable => "\\String".to_string(),
+            TokenKind::Number => "\\Num".to_string(),
+            TokenKind::End => "EOL".to_string(),
+            TokenKind::Comma => "`,`".to_string(),
+            _ => kind.to_string().trim_matches('`').to_string(),
+        };
+    }
+
+    impl fmt::Display for Symbol {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            return match self {
+                T(kind) => f.write_str(&terminal(*kind)),
+                N(nt) => f.write_str(nt.name()),
+            };
+        }
+    }
+
+    /// `T' → * T`, or `T' → ε`.
+    impl fmt::Display for Production {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{} →", self.lhs.name())?;
+            if self.rhs.is_empty() {
+                return f.write_str(" ε");
+            }
+            for symbol in self.rhs {
+                write!(f, " {}", symbol)?;
+            }
+            return Ok(());
+        }
+    }
+
+    /// Nullable, FIRST and FOLLOW sets by `NonTerminal`, tokens listed in the order they
+    /// first occur in the productions.
+    #[derive(Debug, Clone)]
+    pub struct Sets {
+        pub nullable: Vec<bool>,
+        pub first: Vec<Vec<TokenKind>>,
+        pub follow: Vec<Vec<TokenKind>>,
+    }
+
+    /// The sets of `PRODUCTIONS`, computed on first use.
+    pub fn sets() -> &'static Sets {
+        static SETS: OnceLock<Sets> = OnceLock::new();
+        return SETS.get_or_init(|| Sets::compute(PRODUCTIONS, NT::S));
+    }
+
+    /// Terminals of `productions` in the order they first occur, then the end of input.
+    pub fn terminals(productions: &[Production]) -> Vec<TokenKind> {
+        let mut terminals = Vec::new();
+        for symbol in productions.iter().flat_map(|p| p.rhs) {
+            if let T(kind) = symbol {
+                if !terminals.contains(kind) {
+                    terminals.push(*kind);
+                }
+            }
+        }
+        terminals.push(TokenKind::End);
+        return terminals;
+    }
+
+    /// Adds `kinds` to `set`; whether it grew.
+    fn union(set: &mut Vec<TokenKind>, kinds: &[TokenKind]) -> bool {
+        let len = set.len();
+        for kind in kinds {
+            if !set.contains(kind) {
+                set.push(*kind);
+            }
+        }
+        return set.len() > len;
+    }
+
+    impl Sets {
+        /// Iterates the usual equations to a fixed point.
+        pub fn compute(productions: &[Production], start: NonTerminal) -> Sets {
+            let n = NonTerminal::ALL.len();
+            let mut sets = Sets { nullable: vec![false; n], first: vec![Vec::new(); n], follow: vec![Vec::new(); n] };
+
+            let mut changed = true;
+            while changed {
+                changed = false;
+                for p in productions {
+                    let (first, nullable) = sets.first_of(p.rhs);
+                    changed |= union(&mut sets.first[p.lhs as usize], &first);
+                    if nullable && !sets.nullable[p.lhs as usize] {
+                        sets.nullable[p.lhs as usize] = true;
+                        changed = true;
+                    }
+                }
+            }
+
+            sets.follow[start as usize].push(TokenKind::End);
+            changed = true;
+            while changed {
+                changed = false;
+                for p in productions {
+                    for (i, symbol) in p.rhs.iter().enumerate() {
+                        let N(nt) = symbol else {
+                            continue;
+                        };
+                        let (mut follow, nullable) = sets.first_of(&p.rhs[i + 1..]);
+                        if nullable {
+                            follow.extend_from_slice(&sets.follow[p.lhs as usize]);
+                        }
+                        changed |= union(&mut sets.follow[*nt as usize], &follow);
+                    }
+                }
+            }
+
+            let order = terminals(productions);
+            let position = |kind: &TokenKind| order.iter().position(|k| k == kind);
+            for set in sets.first.iter_mut().chain(sets.follow.iter_mut()) {
+                set.sort_by_key(position);
+            }
+            return sets;
+        }
+
+        /// FIRST set of a sequence of symbols, and whether it derives ε.
+        pub fn first_of(&self, symbols: &[Symbol]) -> (Vec<TokenKind>, bool) {
+            let mut first = Vec::new();
+            for symbol in symbols {
+                match symbol {
+                    T(kind) => {
+                        union(&mut first, &[*kind]);
+                        return (first, false);
+                    }
+                    N(nt) => {
+                        union(&mut first, &self.first[*nt as usize]);
+                        if !self.nullable[*nt as usize] {
+                            return (first, false);
+                        }
+                    }
+                }
+            }
+            return (first, true);
+        }
+    }
+
+    /// The LL(1) prediction table: the production to expand a non-terminal with, given
+    /// the current token.
+    #[derive(Debug, Clone)]
+    pub struct Table {
+        predict: HashMap<(NonTerminal, TokenKind), &'static Production>,
+    }
+
+    /// The table of `PRODUCTIONS`, computed on first use.
+    pub fn table() -> &'static Table {
+        static TABLE: OnceLock<Table> = OnceLock::new();
+        return TABLE.get_or_init(|| Table::build(PRODUCTIONS, sets()));
+    }
+
+    impl Table {
+        /// `A → α` is predicted on FIRST(α), and on FOLLOW(A) if α derives ε. Should two
+        /// productions be predicted on the same token, the one listed first wins; see
+        /// `conflicts`.
+        pub fn build(productions: &'static [Production], sets: &Sets) -> Table {
+            let mut predict = HashMap::new();
+            for p in productions {
+                let (mut tokens, nullable) = sets.first_of(p.rhs);
+                if nullable {
+                    tokens.extend_from_slice(&sets.follow[p.lhs as usize]);
+                }
+                for token in tokens {
+                    predict.entry((p.lhs, token)).or_insert(p);
+                }
+            }
+            return Table { predict };
+        }
+
+        pub fn predict(&self, nt: NonTerminal, token: TokenKind) -> Option<&'static Production> {
+            return self.predict.get(&(nt, token)).copied();
+        }
+    }
+
+    #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+    pub enum ConflictKind {
+        /// Both productions derive a string starting with the token.
+        FirstFirst,
+        /// One production derives ε, the token follows the non-terminal and the other
+        /// production derives a string starting with it.
+        FirstFollow,
+    }
+
+    /// Two productions of the same non-terminal predicted on the same token, which
+    /// makes the grammar not LL(1).
+    #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+    pub struct Conflict {
+        pub kind: ConflictKind,
+        pub token: TokenKind,
+        pub productions: (&'static Production, &'static Production),
+    }
+
+    impl fmt::Display for Conflict {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let kind = match self.kind {
+                ConflictKind::FirstFirst => "FIRST/FIRST",
+                ConflictKind::FirstFollow => "FIRST/FOLLOW",
+            };
+            let (p, q) = self.productions;
+            return write!(f, "{} conflict in {} on {}: `{}` and `{}`", kind, p.lhs.name(), terminal(self.token), p, q);
+        }
+    }
+
+    /// Every pair of productions of a non-terminal predicted on the same token, once per
+    /// token. None for the grammar of README.md.
+    pub fn conflicts(productions: &'static [Production], sets: &Sets) -> Vec<Conflict> {
+        let mut conflicts = Vec::new();
+        for (i, p) in productions.iter().enumerate() {
+            let (p_first, p_nullable) = sets.first_of(p.rhs);
+            for q in productions[i + 1..].iter().filter(|q| q.lhs == p.lhs) {
+                let (q_first, q_nullable) = sets.first_of(q.rhs);
+                for token in terminals(productions) {
+                    let follows = sets.follow[p.lhs as usize].contains(&token);
+                    let in_p = p_first.contains(&token) || (p_nullable && follows);
+                    let in_q = q_first.contains(&token) || (q_nullable && follows);
+                    if !in_p || !in_q {
+                        continue;
+                    }
+                    let kind = if p_first.contains(&token) && q_first.contains(&token) {
+                        ConflictKind::FirstFirst
+                    } else {
+                        ConflictKind::FirstFollow
+                    };
+                    conflicts.push(Conflict { kind, token, productions: (p, q) });
+                }
+            }
+        }
+        return conflicts;
+    }
+
+    /// The FIRST/FOLLOW table of README.md, ε listed last in FIRST sets.
+    pub fn markdown(sets: &Sets) -> String {
+        let mut rows = vec![["Non-terminal".to_string(), "FIRST".to_string(), "FOLLOW".to_string()]];
+        for nt in NonTerminal::ALL {
+            let mut first: Vec<String> = sets.first[nt as usize].iter().map(|k| terminal(*k)).collect();
+            if sets.nullable[nt as usize] {
+                first.push("ε".to_string());
+            }
+            let follow: Vec<String> = sets.follow[nt as usize].iter().map(|k| terminal(*k)).collect();
+            rows.push([nt.name().to_string(), first.join(", "), follow.join(", ")]);
+        }
+
+        let widths: Vec<usize> = (0..3).map(|i| rows.iter().map(|r| r[i].chars().count()).max().unwrap_or(0)).collect();
+        let mut out = String::new();
+        for (n, row) in rows.iter().enumerate() {
+            for (cell, width) in row.iter().zip(&widths) {
+                out.push_str(&format!("| {}{} ", cell, " ".repeat(width - cell.chars().count())));
+            }
+            out.push_str("|\n");
+            if n == 0 {
+                for width in &widths {
+                    out.push_str(&format!("|{}", "-".repeat(width + 2)));
+                }
+                out.push_str("|\n");
+            }
+        }
+        return out;
+    }
+}
@@ -0,0 +1,112 @@
+pub mod graphviz {
+    //! The derivation tree in GraphViz DOT, children left to right and every kind of
+    //! node drawn its own way.
+
+    use std::fmt;
+    use std::fmt::Write;
+
+    use petgraph::graph::NodeIndex;
+
+    use crate::parser::parser::{Parser, Tree, ERROR};
+
+    /// Direction the tree grows in, from the root to the leaves.
+    #[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+    pub enum RankDir {
+        #[default]
+        TopBottom,
+        LeftRight,
+        BottomTop,
+        RightLeft,
+    }
+
+    impl fmt::Display for RankDir {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            return f.write_str(match self {
+                RankDir::TopBottom => "TB",
+                RankDir::LeftRight => "LR",
+                RankDir::BottomTop => "BT",
+                RankDir::RightLeft => "RL",
+            });
+        }
+    }
+
+    #[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+    pub struct DotOptions {
+        pub rankdir: RankDir,
+        /// Leaves out the ε leaves of ε-productions.
+        pub hide_epsilon: bool,
+    }
+
+    #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+    pub enum NodeKind {
+        NonTerminal,
+        Terminal,
+        Epsilon,
+        Error,
+    }
+
+    impl NodeKind {
+        /// Kind of a node of the tree `parser` built. Only terminals have a span: an
+        /// identifier may well be called `S` or `ε`.
+        pub fn of(parser: &Parser, node: NodeIndex) -> NodeKind {
+            if parser.spans.contains_key(&node) {
+                return NodeKind::Terminal;
+            }
+            return match parser.graph[node].as_str() {
+                ERROR => NodeKind::Error,
+                "ε" => NodeKind::Epsilon,
+                _ => NodeKind::NonTerminal,
+            };
+        }
+
+        /// Fill and stroke colours, the same in every exporter.
+        pub fn colors(self) -> (&'static str, &'static str) {
+            return match self {
+                NodeKind::NonTerminal => ("#dae8fc", "#6c8ebf"),
+                NodeKind::Terminal => ("#d5e8d4", "#82b366"),
+                NodeKind::Epsilon => ("#ffffff", "#999999"),
+                NodeKind::Error => ("#f8cecc", "#b85450"),
+            };
+        }
+
+        fn attributes(self) -> String {
+            let (fill, stroke) = self.colors();
+            return match self {
+                NodeKind::NonTerminal => format!("shape=ellipse, style=filled, fillcolor=\"{}\", color=\"{}\"", fill, stroke),
+                NodeKind::Terminal => format!("shape=box, style=\"rounded,filled\", fillcolor=\"{}\", color=\"{}\"", fill, stroke),
+                NodeKind::Epsilon => format!("shape=plaintext, fontcolor=\"{}\"", stroke),
+                NodeKind::Error => format!("shape=octagon, style=filled, fillcolor=\"{}\", color=\"{}\"", fill, stroke),
+            };
+        }
+    }
+
+    /// `label` as a DOT string, quotes included.
+    fn quote(label: &str) -> String {
+        return format!("\"{}\"", label.replace('\\', "\\\\").replace('"', "\\\""));
+    }
+
+    /// The tree below `root` in DOT. Nodes are listed depth first and `ordering=out`
+    /// keeps the children of every node in derivation order.
+    pub fn to_dot(parser: &Parser, root: NodeIndex, options: &DotOptions) -> String {
+        let graph = &parser.graph;
+        let mut nodes = String::new();
+        let mut edges = String::new();
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            let kind = NodeKind::of(parser, node);
+            writeln!(nodes, "    {} [label={}, {}]", node.index(), quote(&graph[node]), kind.attributes()).unwrap();
+            let children: Vec<NodeIndex> = Tree::children(graph, node)
+                .into_iter()
+                .filter(|child| !options.hide_epsilon || NodeKind::of(parser, *child) != NodeKind::Epsilon)
+                .collect();
+            for child in &children {
+                writeln!(edges, "    {} -> {}", node.index(), child.index()).unwrap();
+            }
+            stack.extend(children.into_iter().rev());
+        }
+        return format!(
+            "digraph {{\n    graph [ordering=out, rankdir={}]\n    edge [arrowhead=none]\n{}{}}}\n",
+            options.rankdir, nodes, edges
+        );
+    }
+}
@@ -1,12 +1,19 @@
-pub(crate) mod lexer {
-    use std::collections::HashMap;
+pub mod lexer {
+    use std::fmt;
+    use std::time::Instant;
+
+    use serde::{Serialize, Serializer};
+
+    use crate::codes::codes;
+    use crate::diagnostic::diagnostic::{Applicability, Diagnostic};
+    use crate::limits::limits::{LimitExceeded, ParseLimits, DEADLINE_INTERVAL};
 
     use crate::lexer::lexer::Constant::{False, Number, True};
     use crate::lexer::lexer::Operations::{Add, And, Div, Mod, Mul, Not, Or, Sub};
     use crate::lexer::lexer::Token::{Colon, Comma, End, Op};
-    use crate::Token::{Const, Lambda, LParen, RParen, Variable};
+    use crate::lexer::lexer::Token::{Const, Lambda, LParen, RParen, Variable};
 
-    #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+    #[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
     pub enum Operations {
         Mod,
         Add,
@@ -20,107 +27,440 @@ pub(crate) mod lexer {
         Not,
     }
 
-    #[derive(Debug, Eq, PartialEq, Clone)]
-    pub enum Constant {
+    #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+    pub enum Constant<'src> {
         True,
         False,
-        Number(String),
+        Number(&'src str),
     }
 
-    #[derive(Debug, Eq, PartialEq, Clone)]
-    pub enum Token {
+    /// A token; names and numbers borrow their text from the source.
+    #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+    pub enum Token<'src> {
         End,
         Lambda,
         Colon,
         Comma,
         LParen,
         RParen,
-        Variable(String),
+        Variable(&'src str),
         Op(Operations),
-        Const(Constant),
+        Const(Constant<'src>),
+    }
+
+    /// A token without its text, as used in FIRST/FOLLOW sets.
+    #[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+    pub enum TokenKind {
+        End,
+        Lambda,
+        Colon,
+        Comma,
+        LParen,
+        RParen,
+        Variable,
+        Number,
+        True,
+        False,
+        Op(Operations),
+    }
+
+    impl Token<'_> {
+        pub fn kind(&self) -> TokenKind {
+            return match self {
+                End => TokenKind::End,
+                Lambda => TokenKind::Lambda,
+                Colon => TokenKind::Colon,
+                Comma => TokenKind::Comma,
+                LParen => TokenKind::LParen,
+                RParen => TokenKind::RParen,
+                Variable(_) => TokenKind::Variable,
+                Op(op) => TokenKind::Op(*op),
+                Const(True) => TokenKind::True,
+                Const(False) => TokenKind::False,
+                Const(Number(_)) => TokenKind::Number,
+            };
+        }
+    }
+
+    impl fmt::Display for TokenKind {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            return match self {
+                TokenKind::End => f.write_str("end of input"),
+                TokenKind::Variable => f.write_str("identifier"),
+                TokenKind::Number => f.write_str("number"),
+                TokenKind::Lambda => f.write_str("`lambda`"),
+                TokenKind::Colon => f.write_str("`:`"),
+                TokenKind::Comma => f.write_str("`,`"),
+                TokenKind::LParen => f.write_str("`(`"),
+                TokenKind::RParen => f.write_str("`)`"),
+                TokenKind::True => f.write_str("`True`"),
+                TokenKind::False => f.write_str("`False`"),
+                TokenKind::Op(op) => write!(f, "`{}`", op),
+            };
+        }
+    }
+
+    /// Serialized as the token text, e.g. `or`, `)`, `identifier` or `end of input`.
+    impl Serialize for TokenKind {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            return serializer.serialize_str(self.to_string().trim_matches('`'));
+        }
     }
 
-    pub struct Lexer {
+    impl fmt::Display for Operations {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            return f.write_str(match self {
+                Mod => "%",
+                Add => "+",
+                Sub => "-",
+                Mul => "*",
+                Div => "//",
+                And => "and",
+                Or => "or",
+                Operations::Eq => "==",
+                Not => "not",
+            });
+        }
+    }
+
+    impl fmt::Display for Token<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            return match self {
+                End => f.write_str("end of input"),
+                Lambda => f.write_str("lambda"),
+                Colon => f.write_str(":"),
+                Comma => f.write_str(","),
+                LParen => f.write_str("("),
+                RParen => f.write_str(")"),
+                Variable(name) => f.write_str(name),
+                Op(op) => write!(f, "{}", op),
+                Const(True) => f.write_str("True"),
+                Const(False) => f.write_str("False"),
+                Const(Number(num)) => f.write_str(num),
+            };
+        }
+    }
+
+    /// Byte range `start..end` of the source.
+    #[derive(Debug, Eq, PartialEq, Copy, Clone, Default, Serialize)]
+    pub struct Span {
+        pub start: usize,
+        pub end: usize,
+    }
+
+    #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+    pub struct SpannedToken<'src> {
+        pub token: Token<'src>,
+        pub span: Span,
+    }
+
+    /// A problem the lexer found and recovered from, e.g. an unexpected character
+    /// that was skipped or `&&` that was read as `and`.
+    #[derive(Debug, Eq, PartialEq, Clone)]
+    pub struct LexError(pub Diagnostic);
+
+    impl fmt::Display for LexError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            return f.write_str(&self.0.message);
+        }
+    }
+
+    impl std::error::Error for LexError {}
+
+    /// Operators from other languages that users write by habit: the text, the token
+    /// to carry on with, and the Python spelling if there is one.
+    const FOREIGN_OPERATORS: &[(&str, Token<'static>, Option<&str>)] = &[
+        ("&&", Op(And), Some("and")),
+        ("||", Op(Or), Some("or")),
+        ("!=", Op(Operations::Eq), None),
+        ("!", Op(Not), Some("not")),
+        ("->", Colon, Some(":")),
+    ];
+
+    pub struct Lexer<'src> {
         cur_char: u8,
         cur_pos: usize,
-        cur_token: Token,
-        str: String,
+        char_pos: usize,
+        cur_token: Token<'src>,
+        cur_span: Span,
+        str: &'src str,
+        pub diagnostics: Vec<Diagnostic>,
+        pub limits: ParseLimits,
+        tokens: usize,
+        /// Set once a limit is hit; from then on the only token is `End`.
+        pub exceeded: Option<LimitExceeded>,
+        /// How many of `diagnostics` the iterator has yielded.
+        reported: usize,
     }
 
-    impl Lexer {
-        pub fn get(str: String) -> Lexer {
-            let mut x = Lexer { cur_char: 0, cur_pos: 0, cur_token: Token::End, str };
+    /// Every token of `src` up to the end of input, skipping what the lexer reports.
+    pub fn tokenize(src: &str) -> Vec<SpannedToken<'_>> {
+        return Lexer::get(src).filter_map(Result::ok).collect();
+    }
+
+    impl<'src> Lexer<'src> {
+        pub fn get(str: &'src str) -> Lexer<'src> {
+            return Lexer::with_limits(str, ParseLimits::default());
+        }
+
+        pub fn with_limits(str: &'src str, limits: ParseLimits) -> Lexer<'src> {
+            let mut x = Lexer {
+                cur_char: 0,
+                cur_pos: 0,
+                char_pos: 0,
+                cur_token: Token::End,
+                cur_span: Span::default(),
+                str,
+                diagnostics: Vec::new(),
+                limits,
+                tokens: 0,
+                exceeded: None,
+                reported: 0,
+            };
+            if x.str.len() > limits.max_source_length {
+                x.stop(LimitExceeded::SourceLength(limits.max_source_length));
+                return x;
+            }
             x.next_char();
             x.next_token();
             return x;
         }
 
+        /// Reports `limit` at the current token and ends the input there.
+        pub fn stop(&mut self, limit: LimitExceeded) {
+            if self.exceeded.is_some() {
+                return;
+            }
+            self.exceeded = Some(limit);
+            self.diagnostics.push(limit.diagnostic(self.cur_span));
+            self.cur_token = End;
+            self.cur_span = Span { start: self.cur_span.start, end: self.cur_span.start };
+        }
+
         fn is_blank(c: u8) -> bool {
-            return c == ' ' as u8 || c == '\r' as u8 || c == '\n' as u8;
+            return c == b' ' || c == b'\r' || c == b'\n';
         }
 
         fn is_digit(c: u8) -> bool {
-            return c >= '0' as u8 && c <= '9' as u8;
+            return c.is_ascii_digit();
         }
 
         fn is_separator(c: u8) -> bool {
-            let seps = ['\0',
-                '*', '/', '+', '-', '%',
-                ',', ':',
-                '=',
-                '(', ')'];
-            return Lexer::is_blank(c) || seps.contains(&(c as char));
+            return Lexer::is_blank(c) || matches!(c, b'*' | b'/' | b'+' | b'-' | b'%' | b',' | b':' | b'=' | b'(' | b')');
         }
 
         fn is_letter(c: u8) -> bool {
-            return (c >= 'a' as u8 && c <= 'z' as u8) || (c >= 'A' as u8 && c <= 'Z' as u8);
+            return c.is_ascii_alphabetic() || c == b'_';
+        }
+
+        /// Characters of identifiers and numbers; non-ASCII ones are kept as they are.
+        fn is_word(c: u8) -> bool {
+            return c.is_ascii_alphanumeric() || c == b'_' || c >= 0x80;
+        }
+
+        fn foreign_operator(&self) -> Option<&'static (&'static str, Token<'static>, Option<&'static str>)> {
+            if !matches!(self.cur_char, b'&' | b'|' | b'!' | b'-') {
+                return None;
+            }
+            let rest = &self.str.as_bytes()[self.char_pos..];
+            return FOREIGN_OPERATORS.iter().find(|(text, _, _)| rest.starts_with(text.as_bytes()));
+        }
+
+        /// `word` padded with spaces where it would otherwise stick to an identifier,
+        /// so that replacing `a&&b` gives `a and b` rather than `aandb`.
+        fn spaced(&self, span: Span, word: &str) -> String {
+            let bytes = self.str.as_bytes();
+            let mut result = word.to_string();
+            if word.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                if span.start > 0 && Lexer::is_word(bytes[span.start - 1]) {
+                    result.insert(0, ' ');
+                }
+                if span.end < bytes.len() && Lexer::is_word(bytes[span.end]) {
+                    result.push(' ');
+                }
+            }
+            return result;
+        }
+
+        /// Numbers are decimal integers written as in Python: digits only, and no
+        /// leading zeros unless the number is zero. The word is kept as a number either way.
+        fn check_number(&self, word: &str, span: Span) -> Option<Diagnostic> {
+            if !word.bytes().all(Lexer::is_digit) {
+                return Some(Diagnostic::error("invalid decimal literal".to_string(), span)
+                    .with_code(codes::INVALID_NUMBER)
+                    .with_label("invalid decimal literal")
+                    .with_note("numbers are decimal integers such as `42`, names cannot start with a digit"));
+            }
+            let digits = word.trim_start_matches('0');
+            if word.starts_with('0') && !digits.is_empty() {
+                return Some(Diagnostic::error("leading zeros in decimal integer literals are not permitted".to_string(), span)
+                    .with_code(codes::INVALID_NUMBER)
+                    .with_label("leading zero")
+                    .with_suggestion("remove the leading zeros", span, digits, Applicability::MachineApplicable));
+            }
+            return None;
         }
 
         pub fn has_more_symbols(&self) -> bool {
             return self.cur_pos < self.str.len();
         }
 
+        /// True once every character has been read. The end is a position: a NUL byte
+        /// in the source is just another unexpected character.
+        fn at_end(&self) -> bool {
+            return self.char_pos == self.str.len();
+        }
+
         fn next_char(&mut self) {
             if !self.has_more_symbols() {
                 self.cur_char = 0;
+                self.char_pos = self.str.len();
                 return;
             }
             self.cur_char = self.str.as_bytes()[self.cur_pos];
-            self.cur_pos = self.cur_pos + 1;
+            self.char_pos = self.cur_pos;
+            self.cur_pos += 1;
         }
 
-        fn expect_str(&mut self, x: &str) {
-            let mut i = 0;
-
-            let s = x.as_bytes();
-            while i < s.len() {
+        /// Consumes `x` if the input continues with it right after the current character.
+        fn expect_str(&mut self, x: &str) -> bool {
+            if !self.str.as_bytes()[self.cur_pos..].starts_with(x.as_bytes()) {
+                return false;
+            }
+            for _ in 0..x.len() {
                 self.next_char();
-                assert_eq!(s[i], self.cur_char);
-                i = i + 1;
             }
+            return true;
+        }
+
+        /// Span from `start` up to the current character, at least one byte long.
+        fn span_from(&self, start: usize) -> Span {
+            return Span { start, end: self.char_pos.max(start + 1).min(self.str.len()) };
+        }
+
+        /// Skips blanks and characters no token can start with, reporting the latter.
+        fn skip_garbage(&mut self) {
+            loop {
+                while Lexer::is_blank(self.cur_char) {
+                    self.next_char();
+                }
+                let c = self.cur_char;
+                if self.at_end() || Lexer::is_separator(c) || Lexer::is_letter(c) || Lexer::is_digit(c) || self.foreign_operator().is_some() {
+                    return;
+                }
+
+                let start = self.char_pos;
+                let ch = self.str[start..].chars().next().unwrap();
+                for _ in 0..ch.len_utf8() {
+                    self.next_char();
+                }
+                let shown = if ch.is_control() { ch.escape_default().to_string() } else { ch.to_string() };
+                let e = Diagnostic::error(format!("unexpected character `{}`", shown), self.span_from(start))
+                    .with_code(codes::UNEXPECTED_CHARACTER)
+                    .with_label("unexpected character");
+                self.diagnostics.push(e);
+            }
+        }
+
+        /// Counts the token about to be lexed against the token limit and the deadline.
+        fn count_token(&mut self) -> Option<LimitExceeded> {
+            if self.at_end() {
+                return None;
+            }
+            self.tokens += 1;
+            if self.tokens > self.limits.max_tokens {
+                return Some(LimitExceeded::Tokens(self.limits.max_tokens));
+            }
+            // The first token is checked too, for a deadline that passed before parsing.
+            if self.tokens % DEADLINE_INTERVAL == 1 && self.limits.deadline.is_some_and(|deadline| Instant::now() > deadline) {
+                return Some(LimitExceeded::Deadline);
+            }
+            return None;
         }
 
         pub fn next_token(&mut self) {
-            while Lexer::is_blank(self.cur_char) {
-                self.next_char();
+            if self.exceeded.is_some() {
+                return;
+            }
+            self.skip_garbage();
+            if let Some(limit) = self.count_token() {
+                self.cur_span = Span { start: self.char_pos, end: self.char_pos };
+                self.stop(limit);
+                return;
             }
 
+            let start = self.char_pos;
             let cur_char = self.cur_char;
+            if self.at_end() {
+                self.cur_token = End;
+                self.cur_span = Span { start, end: start };
+                return;
+            }
+
+            if let Some((text, tok, python)) = self.foreign_operator() {
+                for _ in 0..text.len() {
+                    self.next_char();
+                }
+                self.cur_token = *tok;
+                self.cur_span = Span { start, end: self.char_pos };
+                let e = match python {
+                    Some(":") => {
+                        // `x -> y` becomes `x: y`, so the fix takes the blanks before the arrow too.
+                        let mut fix = self.cur_span;
+                        while fix.start > 0 && Lexer::is_blank(self.str.as_bytes()[fix.start - 1]) {
+                            fix.start -= 1;
+                        }
+                        Diagnostic::error(format!("expected `:`, found `{}`", text), self.cur_span)
+                            .with_code(codes::MISSING_COLON)
+                            .with_label("expected `:`")
+                            .with_expected(vec![TokenKind::Colon])
+                            .with_suggestion("lambda parameters are followed by `:`", fix, ":", Applicability::MachineApplicable)
+                    }
+                    Some(python) => Diagnostic::error(format!("`{}` is not a Python operator", text), self.cur_span)
+                        .with_code(codes::FOREIGN_OPERATOR)
+                        .with_label("not a Python operator")
+                        .with_suggestion(&format!("use `{}` instead", python), self.cur_span,
+                                         &self.spaced(self.cur_span, python), Applicability::MachineApplicable),
+                    None => Diagnostic::error(format!("`{}` is not supported", text), self.cur_span)
+                        .with_code(codes::NOT_EQUAL)
+                        .with_label("not supported")
+                        .with_help("negate the comparison instead: `not a == b`"),
+                };
+                self.diagnostics.push(e);
+                return;
+            }
 
             if Lexer::is_separator(cur_char) {
                 self.cur_token = match cur_char as char {
-                    '\0' => End,
                     ',' => Comma,
                     ':' => Colon,
                     '(' => LParen,
                     ')' => RParen,
                     '=' => {
-                        self.expect_str("=");
+                        if !self.expect_str("=") {
+                            let span = self.span_from(start);
+                            let e = Diagnostic::error("expected `==`, found `=`".to_string(), span)
+                                .with_code(codes::ASSIGNMENT)
+                                .with_label("expected `==`")
+                                .with_expected(vec![TokenKind::Op(Operations::Eq)])
+                                .with_note("assignment is not an expression")
+                                .with_suggestion("use `==` to compare values", span, "==", Applicability::MachineApplicable);
+                            self.diagnostics.push(e);
+                        }
                         Op(Operations::Eq)
                     }
                     '*' => Op(Mul),
                     '/' => {
-                        self.expect_str("/");
+                        if !self.expect_str("/") {
+                            let span = self.span_from(start);
+                            let e = Diagnostic::error("expected `//`, found `/`".to_string(), span)
+                                .with_code(codes::TRUE_DIVISION)
+                                .with_label("expected `//`")
+                                .with_expected(vec![TokenKind::Op(Div)])
+                                .with_note("true division is not supported")
+                                .with_suggestion("use `//` for floor division", span, "//", Applicability::MaybeIncorrect);
+                            self.diagnostics.push(e);
+                        }
                         Op(Div)
                     }
                     '+' => Op(Add),
@@ -129,44 +469,78 @@ pub(crate) mod lexer {
                     _ => panic!("Unknown separator")
                 };
                 self.next_char();
+                self.cur_span = Span { start, end: self.char_pos };
                 return;
             }
 
-            let mut is_literal = false;
-            if Lexer::is_letter(self.cur_char) {
-                is_literal = true;
-            } else if Lexer::is_digit(self.cur_char) {
-                is_literal = false;
-            } else {
-                panic!("Unexpected character '{}' at pos {}", self.cur_char as char, self.cur_pos);
-            }
-            let mut cur_tok = "".to_string();
-
-            while !Lexer::is_separator(self.cur_char) {
-                cur_tok = cur_tok + (self.cur_char as char).to_string().as_str();
+            let is_literal = Lexer::is_letter(self.cur_char);
+            while Lexer::is_word(self.cur_char) {
                 self.next_char();
             }
+            self.cur_span = Span { start, end: self.char_pos };
+            let word = &self.str[start..self.char_pos];
 
             if is_literal {
-                let keywords = HashMap::from([
-                    ("lambda", Lambda),
-                    ("not", Op(Not)),
-                    ("and", Op(And)),
-                    ("or", Op(Or)),
-                    ("True", Const(True)),
-                    ("False", Const(False))
-                ]);
-                self.cur_token = match keywords.get(cur_tok.as_str()) {
-                    None => Variable(cur_tok),
-                    Some(tok) => tok.clone()
+                self.cur_token = match word {
+                    "lambda" => Lambda,
+                    "not" => Op(Not),
+                    "and" => Op(And),
+                    "or" => Op(Or),
+                    "True" => Const(True),
+                    "False" => Const(False),
+                    _ => Variable(word),
                 };
             } else {
-                self.cur_token = Const(Number(cur_tok));
+                if let Some(e) = self.check_number(word, self.cur_span) {
+                    self.diagnostics.push(e);
+                }
+                self.cur_token = Const(Number(word));
             }
         }
 
-        pub fn get_token(&self) -> Token {
-            return self.cur_token.clone();
+        pub fn get_token(&self) -> Token<'src> {
+            return self.cur_token;
+        }
+
+        pub fn get_span(&self) -> Span {
+            return self.cur_span;
+        }
+
+        /// The current token, which `next` yields once the errors before it are out.
+        pub fn peek(&self) -> SpannedToken<'src> {
+            return SpannedToken { token: self.cur_token, span: self.cur_span };
+        }
+
+        /// The token `n` places after the current one. The lexer lexes ahead and then
+        /// goes back, so nothing it reports on the way is kept.
+        pub fn peek_nth(&mut self, n: usize) -> SpannedToken<'src> {
+            let saved = (self.cur_char, self.cur_pos, self.char_pos, self.cur_token, self.cur_span, self.tokens, self.exceeded);
+            let reported = self.diagnostics.len();
+            for _ in 0..n {
+                self.next_token();
+            }
+            let result = self.peek();
+            (self.cur_char, self.cur_pos, self.char_pos, self.cur_token, self.cur_span, self.tokens, self.exceeded) = saved;
+            self.diagnostics.truncate(reported);
+            return result;
+        }
+    }
+
+    /// Yields every token but `End`, each preceded by the errors found while lexing it.
+    impl<'src> Iterator for Lexer<'src> {
+        type Item = Result<SpannedToken<'src>, LexError>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            if let Some(e) = self.diagnostics.get(self.reported) {
+                self.reported += 1;
+                return Some(Err(LexError(e.clone())));
+            }
+            if self.cur_token == End {
+                return None;
+            }
+            let tok = self.peek();
+            self.next_token();
+            return Some(Ok(tok));
         }
     }
 }
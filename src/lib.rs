@@ -0,0 +1,18 @@
+#![allow(clippy::needless_return, clippy::module_inception)]
+
+pub mod codes;
+pub mod diagnostic;
+pub mod lexer;
+pub mod limits;
+pub mod grammar;
+pub mod parser;
+pub mod ast;
+pub mod visit;
+pub mod lower;
+pub mod pratt;
+pub mod unparser;
+pub mod formatter;
+pub mod generator;
+pub mod graphviz;
+pub mod tree;
+pub mod diagram;
@@ -0,0 +1,82 @@
+pub mod limits {
+    use std::fmt;
+    use std::time::Instant;
+
+    use crate::codes::codes;
+    use crate::diagnostic::diagnostic::Diagnostic;
+    use crate::lexer::lexer::Span;
+
+    /// Resource guards for parsing untrusted input. The default only limits the
+    /// nesting depth, which protects the stack; everything else is unbounded.
+    #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+    pub struct ParseLimits {
+        /// Longest source accepted, in bytes.
+        pub max_source_length: usize,
+        /// Most tokens the lexer produces, end of input excluded.
+        pub max_tokens: usize,
+        /// Most nodes of the derivation tree.
+        pub max_nodes: usize,
+        /// Most `(` and `lambda` enclosing a token.
+        pub max_depth: usize,
+        /// Parsing stops once this moment has passed. Checked at the first token and
+        /// every `DEADLINE_INTERVAL` tokens after it.
+        pub deadline: Option<Instant>,
+    }
+
+    /// How many `(` and `lambda` may be nested by default. Every level takes a dozen
+    /// stack frames, this keeps a debug build well within a 2 MiB thread stack.
+    pub const DEFAULT_MAX_DEPTH: usize = 200;
+
+    /// Reading the clock for every token would cost more than lexing it.
+    pub const DEADLINE_INTERVAL: usize = 256;
+
+    impl Default for ParseLimits {
+        fn default() -> ParseLimits {
+            return ParseLimits {
+                max_source_length: usize::MAX,
+                max_tokens: usize::MAX,
+                max_nodes: usize::MAX,
+                max_depth: DEFAULT_MAX_DEPTH,
+                deadline: None,
+            };
+        }
+    }
+
+    /// The limit that stopped parsing. Nothing after the point where it was hit
+    /// is lexed or parsed.
+    #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+    pub enum LimitExceeded {
+        SourceLength(usize),
+        Tokens(usize),
+        Nodes(usize),
+        Depth(usize),
+        Deadline,
+    }
+
+    impl fmt::Display for LimitExceeded {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            return match self {
+                LimitExceeded::SourceLength(n) => write!(f, "input is longer than the limit of {} bytes", n),
+                LimitExceeded::Tokens(n) => write!(f, "input has more than the limit of {} tokens", n),
+                LimitExceeded::Nodes(n) => write!(f, "derivation tree has more than the limit of {} nodes", n),
+                LimitExceeded::Depth(n) => write!(f, "nesting exceeds the limit of {} levels", n),
+                LimitExceeded::Deadline => write!(f, "parsing did not finish before the deadline"),
+            };
+        }
+    }
+
+    impl LimitExceeded {
+        /// The error reported at `span`, where parsing stopped.
+        pub fn diagnostic(self, span: Span) -> Diagnostic {
+            return match self {
+                LimitExceeded::Depth(_) => Diagnostic::error(self.to_string(), span)
+                    .with_code(codes::TOO_DEEP)
+                    .with_label("nested too deeply")
+                    .with_note("every `(` and `lambda` opens a level"),
+                _ => Diagnostic::error(self.to_string(), span)
+                    .with_code(codes::LIMIT_EXCEEDED)
+                    .with_label("parsing stopped here"),
+            };
+        }
+    }
+}
@@ -0,0 +1,389 @@
+pub mod lower {
+    use petgraph::graph::NodeIndex;
+
+    use crate::ast::ast::{Ast, Node, NodeId, Symbol};
+    use crate::lexer::lexer::{Operations, Span};
+    use crate::parser::parser::{Parser, Tree, ERROR};
+
+    /// An AST lowered from a derivation tree.
+    pub struct Lowered {
+        pub ast: Ast,
+        /// Derivation node of every AST node, by `NodeId`.
+        origins: Vec<NodeIndex>,
+    }
+
+    impl Lowered {
+        /// The derivation node `id` was lowered from: `S` for a lambda, the chain
+        /// (`E`, `O`, `N`) for `BoolOp` and `Compare`, the tail holding the operator
+        /// (`Qp`, `Tp`) for `BinOp`, `A` or `F` for `UnaryOp` and the terminal leaf for
+        /// names and constants.
+        pub fn origin(&self, id: NodeId) -> NodeIndex {
+            return self.origins[id.index()];
+        }
+    }
+
+    /// Lowers the derivation tree at `root` to an AST: chains of a single child are
+    /// collapsed, ε leaves and parentheses dropped, and every `X X'` tail pair becomes
+    /// an operator node. `+`, `-`, `*`, `//` and `%` associate to the left, `or` and
+    /// `and` operands are gathered in one `BoolOp` and `==` ones in one `Compare`, as
+    /// Python does. Error nodes of the derivation become `Node::Error`.
+    pub fn lower(parser: &Parser, root: NodeIndex) -> Lowered {
+        let mut lowering = Lowering { parser, lowered: Lowered { ast: Ast::new(), origins: Vec::new() }, last_end: 0, end: 0 };
+        let root = lowering.run(root);
+        lowering.lowered.ast.set_root(root);
+        return lowering.lowered;
+    }
+
+    /// Work left to do, on a stack: a node is lowered once the terminals before it are,
+    /// and a `Build*` step runs once the nodes it is made of are.
+    enum Task {
+        /// Lowers a node, leaving a `Value::Node`.
+        Node(NodeIndex),
+        /// Lowers a node, leaving a `Value::Outer` with the span of all its tokens.
+        Outer(NodeIndex),
+        EndOuter { start: usize },
+        Params(NodeIndex),
+        Terminal(NodeIndex),
+        Skip(NodeIndex),
+        /// An error node for a chain operand that is missing.
+        Missing(NodeIndex),
+        /// After the inside of `( S' )`: moves past the `)` and what recovery put before it.
+        EndParens(Vec<NodeIndex>),
+        BuildUnary { node: NodeIndex, op: Operations, start: usize },
+        BuildLambda { node: NodeIndex, start: usize, base: usize },
+        /// The operator of every operand but the first, with the tail it comes from.
+        BuildChain { node: NodeIndex, operators: Vec<(Operations, NodeIndex)>, base: usize },
+    }
+
+    /// What a task leaves for the `Build*` step above it.
+    enum Value {
+        Node(NodeId),
+        Outer(NodeId, Span),
+        Params(Vec<Symbol>),
+    }
+
+    struct Lowering<'p, 'a> {
+        parser: &'p Parser<'a>,
+        lowered: Lowered,
+        /// End of the last terminal lowered, where an empty error node is put.
+        last_end: usize,
+        /// End of the last terminal that is part of a node, not skipped by recovery.
+        end: usize,
+    }
+
+    impl<'p> Lowering<'p, '_> {
+        fn label(&self, node: NodeIndex) -> &'p str {
+            return self.parser.graph[node].as_str();
+        }
+
+        /// Label of a non-terminal, ε or error node; `None` for a terminal, since a
+        /// variable may well be called `V` or `E`.
+        fn symbol(&self, node: NodeIndex) -> Option<&'p str> {
+            if self.parser.spans.contains_key(&node) {
+                return None;
+            }
+            return Some(self.label(node));
+        }
+
+        fn children(&self, node: NodeIndex) -> Vec<NodeIndex> {
+            return Tree::children(&self.parser.graph, node);
+        }
+
+        fn add(&mut self, node: Node, span: Span, origin: NodeIndex) -> NodeId {
+            self.lowered.origins.push(origin);
+            return self.lowered.ast.add(node, span);
+        }
+
+        fn span(&self, id: NodeId) -> Span {
+            return self.lowered.ast.span(id);
+        }
+
+        /// Span of a terminal leaf.
+        fn terminal(&mut self, leaf: NodeIndex) -> Span {
+            let span = self.parser.spans.get(&leaf).copied().unwrap_or(Span { start: self.last_end, end: self.last_end });
+            self.last_end = span.end;
+            self.end = span.end;
+            return span;
+        }
+
+        /// Lowers the tree at `root` with an explicit stack.
+        fn run(&mut self, root: NodeIndex) -> NodeId {
+            let mut tasks = vec![Task::Node(root)];
+            let mut values = Vec::new();
+            while let Some(task) = tasks.pop() {
+                match task {
+                    Task::Node(node) => self.node(node, &mut tasks, &mut values),
+                    Task::Outer(node) => {
+                        let start = self.first_start(node).unwrap_or(self.last_end);
+                        tasks.push(Task::EndOuter { start });
+                        tasks.push(Task::Node(node));
+                    }
+                    Task::EndOuter { start } => {
+                        let Some(Value::Node(id)) = values.pop() else {
+                            unreachable!("a node was lowered");
+                        };
+                        values.push(Value::Outer(id, Span { start, end: self.end }));
+                    }
+                    Task::Params(node) => values.push(Value::Params(self.params(node))),
+                    Task::Terminal(leaf) => {
+                        self.terminal(leaf);
+                    }
+                    Task::Skip(node) => self.skip(node),
+                    Task::Missing(node) => {
+                        let error = self.error(node);
+                        values.push(Value::Outer(error, self.span(error)));
+                    }
+                    Task::EndParens(rest) => {
+                        for child in rest {
+                            self.skip(child);
+                        }
+                        self.end = self.last_end;
+                    }
+                    Task::BuildUnary { node, op, start } => {
+                        let Some(Value::Outer(operand, outer)) = values.pop() else {
+                            unreachable!("the operand was lowered");
+                        };
+                        let id = self.add(Node::UnaryOp { op, operand }, Span { start, end: outer.end }, node);
+                        values.push(Value::Node(id));
+                    }
+                    Task::BuildLambda { node, start, base } => {
+                        let id = self.build_lambda(node, start, values.split_off(base));
+                        values.push(Value::Node(id));
+                    }
+                    Task::BuildChain { node, operators, base } => {
+                        let id = self.build_chain(node, operators, values.split_off(base));
+                        values.push(Value::Node(id));
+                    }
+                }
+            }
+            let Some(Value::Node(root)) = values.pop() else {
+                unreachable!("the root was lowered");
+            };
+            return root;
+        }
+
+        /// Lowers `node` right away, or schedules the tasks that do.
+        fn node(&mut self, node: NodeIndex, tasks: &mut Vec<Task>, values: &mut Vec<Value>) {
+            let children = self.children(node);
+            match self.label(node) {
+                "S" => self.lambda(node, &children, tasks, values),
+                // S' → S | E, A → N, F → C, C → ( S' )
+                "S'" | "A" | "F" | "C" if children.len() == 1 && self.symbol(children[0]).is_some_and(|label| label != ERROR) => {
+                    tasks.push(Task::Node(children[0]));
+                }
+                "A" | "F" if children.len() == 2 => {
+                    let op = self.operator(children[0]);
+                    let start = self.terminal(children[0]).start;
+                    tasks.push(Task::BuildUnary { node, op, start });
+                    tasks.push(Task::Outer(children[1]));
+                }
+                "C" if children.len() > 1 => {
+                    self.terminal(children[0]);
+                    tasks.push(Task::EndParens(children[2..].to_vec()));
+                    tasks.push(Task::Node(children[1]));
+                }
+                "C" if children.len() == 1 && self.symbol(children[0]).is_none() => {
+                    values.push(Value::Node(self.atom(children[0])));
+                }
+                "E" | "O" | "N" | "Q" | "T" => self.chain(node, tasks, values),
+                _ => values.push(Value::Node(self.error(node))),
+            }
+        }
+
+        /// `lambda V : S'`, with possibly error nodes in between.
+        fn lambda(&mut self, node: NodeIndex, children: &[NodeIndex], tasks: &mut Vec<Task>, values: &mut Vec<Value>) {
+            if children.first().is_none_or(|c| self.symbol(*c).is_some() || self.label(*c) != "lambda") {
+                values.push(Value::Node(self.error(node)));
+                return;
+            }
+            let start = self.terminal(children[0]).start;
+            tasks.push(Task::BuildLambda { node, start, base: values.len() });
+            for child in children[1..].iter().rev() {
+                tasks.push(match self.symbol(*child) {
+                    Some("V") => Task::Params(*child),
+                    Some("S'") => Task::Outer(*child),
+                    _ => Task::Skip(*child),
+                });
+            }
+        }
+
+        /// The lambda, from its parameters and body in `parts`.
+        fn build_lambda(&mut self, node: NodeIndex, start: usize, parts: Vec<Value>) -> NodeId {
+            let mut params = Vec::new();
+            let mut body = None;
+            for part in parts {
+                match part {
+                    Value::Params(names) => params = names,
+                    Value::Outer(id, outer) => body = Some((id, outer)),
+                    Value::Node(_) => unreachable!("lambda parts are lowered with their span"),
+                }
+            }
+            let (body, outer) = match body {
+                Some(body) => body,
+                None => {
+                    let span = Span { start: self.last_end, end: self.last_end };
+                    (self.add(Node::Error, span, node), span)
+                }
+            };
+            let params = self.lowered.ast.param_list(&params);
+            return self.add(Node::Lambda { params, body }, Span { start, end: outer.end }, node);
+        }
+
+        /// Names of `V → var V' | ε`, `V' → , V | ε`.
+        fn params(&mut self, mut node: NodeIndex) -> Vec<Symbol> {
+            let mut params = Vec::new();
+            loop {
+                let children = self.children(node);
+                let mut next = None;
+                for child in children {
+                    match self.symbol(child) {
+                        Some("V") | Some("V'") => next = Some(child),
+                        Some(_) => self.skip(child),
+                        None if self.label(child) == "," => {
+                            self.terminal(child);
+                        }
+                        None => {
+                            let name = self.lowered.ast.intern(self.label(child));
+                            self.terminal(child);
+                            params.push(name);
+                        }
+                    }
+                }
+                match next {
+                    Some(next) => node = next,
+                    None => return params,
+                }
+            }
+        }
+
+        /// `X → Y X'`, `X' → op X | ε` walked in a loop, see `Parser::chain`: the operands
+        /// are lowered in order, with the operator between each two.
+        fn chain(&mut self, node: NodeIndex, tasks: &mut Vec<Task>, values: &[Value]) {
+            let kind = self.label(node);
+            let mut steps = Vec::new();
+            let mut operators = Vec::new();
+            let mut cur = node;
+            loop {
+                let children = self.children(cur);
+                match children.first() {
+                    Some(first) if self.label(*first) != ERROR => steps.push(Task::Outer(*first)),
+                    _ => {
+                        steps.push(Task::Missing(cur));
+                        break;
+                    }
+                }
+                let Some(tail) = children.get(1) else {
+                    break;
+                };
+                let tail_children = self.children(*tail);
+                match tail_children.as_slice() {
+                    [op, next] if self.label(*next) == kind => {
+                        operators.push((self.operator(*op), *tail));
+                        steps.push(Task::Terminal(*op));
+                        cur = *next;
+                    }
+                    _ => {
+                        steps.extend(tail_children.into_iter().map(Task::Skip));
+                        break;
+                    }
+                }
+            }
+            tasks.push(Task::BuildChain { node, operators, base: values.len() });
+            tasks.extend(steps.into_iter().rev());
+        }
+
+        /// The operator node of a chain from its operands in `parts`.
+        fn build_chain(&mut self, node: NodeIndex, operators: Vec<(Operations, NodeIndex)>, parts: Vec<Value>) -> NodeId {
+            let operands: Vec<(NodeId, Span)> = parts
+                .into_iter()
+                .map(|part| match part {
+                    Value::Outer(id, outer) => (id, outer),
+                    _ => unreachable!("chain operands are lowered with their span"),
+                })
+                .collect();
+            if operands.len() == 1 {
+                return operands[0].0;
+            }
+
+            let span = Span { start: operands[0].1.start, end: operands[operands.len() - 1].1.end };
+            let ids: Vec<NodeId> = operands.iter().map(|(id, _)| *id).collect();
+            return match self.label(node) {
+                "E" | "O" => {
+                    let values = self.lowered.ast.list(&ids);
+                    self.add(Node::BoolOp { op: operators[0].0, values }, span, node)
+                }
+                "N" => {
+                    let comparators = self.lowered.ast.list(&ids[1..]);
+                    self.add(Node::Compare { left: ids[0], comparators }, span, node)
+                }
+                _ => {
+                    let (mut left, start) = (ids[0], span.start);
+                    for ((right, outer), (op, tail)) in operands[1..].iter().zip(operators) {
+                        left = self.add(Node::BinOp { left, op, right: *right }, Span { start, end: outer.end }, tail);
+                    }
+                    left
+                }
+            };
+        }
+
+        fn operator(&self, leaf: NodeIndex) -> Operations {
+            return match self.label(leaf) {
+                "or" => Operations::Or,
+                "and" => Operations::And,
+                "not" => Operations::Not,
+                "==" => Operations::Eq,
+                "+" => Operations::Add,
+                "-" => Operations::Sub,
+                "*" => Operations::Mul,
+                "//" => Operations::Div,
+                _ => Operations::Mod,
+            };
+        }
+
+        /// A name, number, `True` or `False`.
+        fn atom(&mut self, leaf: NodeIndex) -> NodeId {
+            let span = self.terminal(leaf);
+            let text = self.label(leaf);
+            let node = match text {
+                "True" => Node::True,
+                "False" => Node::False,
+                _ if text.starts_with(|c: char| c.is_ascii_digit()) => Node::Number(self.lowered.ast.intern(text)),
+                _ => Node::Name(self.lowered.ast.intern(text)),
+            };
+            return self.add(node, span, leaf);
+        }
+
+        /// An error node covering the tokens under `node`.
+        fn error(&mut self, node: NodeIndex) -> NodeId {
+            let start = self.last_end;
+            self.skip(node);
+            let span = Span { start: start.max(self.first_start(node).unwrap_or(start)), end: self.last_end };
+            self.end = span.end;
+            return self.add(Node::Error, span, node);
+        }
+
+        fn first_start(&self, node: NodeIndex) -> Option<usize> {
+            let mut stack = vec![node];
+            while let Some(node) = stack.pop() {
+                if let Some(span) = self.parser.spans.get(&node) {
+                    return Some(span.start);
+                }
+                stack.extend(self.children(node).into_iter().rev());
+            }
+            return None;
+        }
+
+        /// Moves past the terminals under `node` without lowering them.
+        fn skip(&mut self, node: NodeIndex) {
+            let end = self.end;
+            let mut stack = vec![node];
+            while let Some(node) = stack.pop() {
+                if self.parser.spans.contains_key(&node) {
+                    self.terminal(node);
+                }
+                stack.extend(self.children(node).into_iter().rev());
+            }
+            self.end = end;
+        }
+    }
+}
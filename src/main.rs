@@ -1,14 +1,371 @@
-mod lexer;
-mod parser;
+#![allow(clippy::needless_return)]
 
-use petgraph::prelude::Graph;
-use petgraph::dot::Dot;
-use crate::lexer::lexer::{Lexer, Token};
-use crate::parser::parser::Parser;
-use crate::Token::End;
+use std::io::{IsTerminal, Read};
+use std::{env, fs, io, process};
+use std::time::{Duration, Instant};
+
+use lambda_parser::codes::codes::explain;
+use lambda_parser::diagnostic::diagnostic::{to_json, Renderer};
+use lambda_parser::grammar::grammar::{conflicts, markdown, sets, PRODUCTIONS};
+use lambda_parser::diagram::diagram::Diagram;
+use lambda_parser::formatter::formatter::{format_lambda, format_source, DEFAULT_LINE_LENGTH};
+use lambda_parser::generator::generator::{is_identifier, Generator, GeneratorOptions};
+use lambda_parser::graphviz::graphviz::{to_dot, DotOptions, RankDir};
+use lambda_parser::limits::limits::ParseLimits;
+use lambda_parser::lower::lower::lower;
+use lambda_parser::parser::parser::Parser;
+use lambda_parser::pratt::pratt::PrattParser;
+use lambda_parser::tree::tree::{render_ast, render_derivation, TreeOptions};
+
+const EXAMPLE: &str = "lambda: (Hello == 42 or not (Hello == 24)) -92 - 152 * 3 // 2 + 33 -varvarvar * 92 %world";
+
+const USAGE: &str = "usage:
+    lambda-parser [--color auto|always|never] [--error-format human|json] [--emit FORMAT]
+                  [--parser descent|table|pratt] [OUTPUT] [LIMITS] [EXPR]
+    lambda-parser fmt [--check] [--line-length N] [-c EXPR | FILE... | -]
+    lambda-parser grammar
+    lambda-parser generate [--count N] [--seed N] [--max-depth N] [--identifiers A,B...] [--mutate]
+    lambda-parser --explain CODE
+
+formats:
+    dot, tree, mermaid, plantuml                the derivation tree (default: dot)
+    ast, ast-tree, ast-mermaid, ast-plantuml    the AST
+
+output:
+    --rankdir TB|LR|BT|RL  direction of the DOT graph
+    --hide-epsilon         leave ε leaves out of DOT, Mermaid and PlantUML
+    --spans                byte ranges in trees
+    --ascii                draw trees with ASCII instead of box-drawing characters
+    --tree-depth N         deepest level of trees, `…` below (default 100); PlantUML is
+                           whole unless it is given
+
+limits:
+    --max-depth N      nesting of `(` and `lambda` (default 200)
+    --max-length N     source length in bytes
+    --max-tokens N     number of tokens
+    --max-nodes N      nodes of the derivation tree
+    --timeout MS       time spent parsing";
 
 fn main() {
-    let mut a = Parser::get("lambda: (Hello == 42 or not (Hello == 24)) -92 - 152 * 3 // 2 + 33 -varvarvar * 92 %world".to_string());
-    a.S();
-    println!("{}", Dot::new(&a.graph));
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("fmt") => process::exit(fmt(&args[1..])),
+        Some("grammar") => process::exit(grammar()),
+        Some("generate") => process::exit(generate(&args[1..])),
+        Some("--explain") => process::exit(explain_code(args.get(1))),
+        Some("-h") | Some("--help") => println!("{}", USAGE),
+        _ => process::exit(dot(&args)),
+    }
+}
+
+/// Prints the derivation tree (or the AST), error nodes included, and reports every syntax error.
+fn dot(args: &[String]) -> i32 {
+    let mut color = None;
+    let mut json = false;
+    let mut emit = None;
+    let mut parser = "descent";
+    let mut dot = DotOptions::default();
+    let mut tree = TreeOptions::default();
+    let mut plantuml_depth = None;
+    let mut limits = ParseLimits::default();
+    let mut timeout = None;
+    let mut src = EXAMPLE;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--color" => {
+                color = match args.next().map(String::as_str) {
+                    Some("always") => Some(true),
+                    Some("never") => Some(false),
+                    Some("auto") => None,
+                    _ => return usage_error("--color expects auto, always or never"),
+                }
+            }
+            "--error-format" => {
+                json = match args.next().map(String::as_str) {
+                    Some("human") => false,
+                    Some("json") => true,
+                    _ => return usage_error("--error-format expects human or json"),
+                }
+            }
+            "--emit" => {
+                emit = match args.next().map(String::as_str) {
+                    Some(e @ ("dot" | "tree" | "mermaid" | "plantuml" | "ast" | "ast-tree" | "ast-mermaid" | "ast-plantuml")) => Some(e),
+                    _ => return usage_error("--emit expects dot, tree, mermaid, plantuml, ast, ast-tree, ast-mermaid or ast-plantuml"),
+                }
+            }
+            "--parser" => {
+                parser = match args.next().map(String::as_str) {
+                    Some(p @ ("descent" | "table" | "pratt")) => p,
+                    _ => return usage_error("--parser expects descent, table or pratt"),
+                }
+            }
+            "--rankdir" => {
+                dot.rankdir = match args.next().map(String::as_str) {
+                    Some("TB") => RankDir::TopBottom,
+                    Some("LR") => RankDir::LeftRight,
+                    Some("BT") => RankDir::BottomTop,
+                    Some("RL") => RankDir::RightLeft,
+                    _ => return usage_error("--rankdir expects TB, LR, BT or RL"),
+                }
+            }
+            "--hide-epsilon" => dot.hide_epsilon = true,
+            "--spans" => tree.spans = true,
+            "--ascii" => tree.ascii = true,
+            "--tree-depth" => {
+                tree.max_depth = match args.next().map(|n| n.parse()) {
+                    Some(Ok(n)) => n,
+                    _ => return usage_error("--tree-depth expects a number"),
+                };
+                plantuml_depth = Some(tree.max_depth);
+            }
+            "--max-depth" | "--max-length" | "--max-tokens" | "--max-nodes" | "--timeout" => {
+                let n = match args.next().map(|n| n.parse()) {
+                    Some(Ok(n)) => n,
+                    _ => return usage_error(&format!("{} expects a number", arg)),
+                };
+                match arg.as_str() {
+                    "--max-depth" => limits.max_depth = n,
+                    "--max-length" => limits.max_source_length = n,
+                    "--max-tokens" => limits.max_tokens = n,
+                    "--max-nodes" => limits.max_nodes = n,
+                    _ => timeout = Some(Duration::from_millis(n as u64)),
+                }
+            }
+            // No lambda starts with `--`, it is a mistyped option.
+            _ if arg.starts_with("--") => return usage_error(&format!("unexpected argument {}", arg)),
+            _ => src = arg,
+        }
+    }
+
+    limits.deadline = timeout.map(|timeout| Instant::now() + timeout);
+    // Trees go to stdout, so `auto` looks at stdout there and at stderr for diagnostics.
+    tree.color = color.unwrap_or_else(|| io::stdout().is_terminal());
+    let color = color.unwrap_or_else(|| io::stderr().is_terminal());
+    // The Pratt parser builds no derivation tree, only the AST.
+    let diagnostics = if parser == "pratt" {
+        let mut a = PrattParser::with_limits(src, limits);
+        a.parse();
+        match emit {
+            None | Some("ast") => println!("{}", a.ast),
+            Some("ast-tree") => print!("{}", render_ast(&a.ast, &tree)),
+            Some("ast-mermaid") => print!("{}", Diagram::ast(&a.ast).mermaid()),
+            Some("ast-plantuml") => print!("{}", Diagram::ast(&a.ast).plantuml(plantuml_depth)),
+            _ => return usage_error("--parser pratt only emits the AST"),
+        }
+        a.diagnostics
+    } else {
+        let mut a = Parser::with_limits(src, limits);
+        let root = if parser == "table" { a.parse_table() } else { a.parse() };
+        match emit {
+            Some("ast") => println!("{}", lower(&a, root).ast),
+            Some("ast-tree") => print!("{}", render_ast(&lower(&a, root).ast, &tree)),
+            Some("ast-mermaid") => print!("{}", Diagram::ast(&lower(&a, root).ast).mermaid()),
+            Some("ast-plantuml") => print!("{}", Diagram::ast(&lower(&a, root).ast).plantuml(plantuml_depth)),
+            Some("tree") => print!("{}", render_derivation(&a, root, &tree)),
+            Some("mermaid") => print!("{}", Diagram::derivation(&a, root, dot.hide_epsilon).mermaid()),
+            Some("plantuml") => print!("{}", Diagram::derivation(&a, root, dot.hide_epsilon).plantuml(plantuml_depth)),
+            _ => print!("{}", to_dot(&a, root, &dot)),
+        }
+        a.diagnostics
+    };
+
+    let renderer = Renderer { source: src, name: "<input>", color };
+    for e in &diagnostics {
+        if json {
+            eprintln!("{}", to_json(e, src));
+        } else {
+            eprintln!("{}", renderer.render(e));
+        }
+    }
+
+    // Like rustc, point at `--explain` once for all the codes that were reported.
+    let mut codes: Vec<&str> = diagnostics.iter().filter_map(|d| d.code).collect();
+    codes.sort();
+    codes.dedup();
+    if !json && !codes.is_empty() {
+        if codes.len() > 1 {
+            eprintln!("Some errors have detailed explanations: {}.", codes.join(", "));
+            eprintln!("For more information about an error, try `lambda-parser --explain {}`.", codes[0]);
+        } else {
+            eprintln!("For more information about this error, try `lambda-parser --explain {}`.", codes[0]);
+        }
+    }
+    return if diagnostics.iter().any(|d| d.is_error()) { 1 } else { 0 };
+}
+
+/// Prints the FIRST/FOLLOW table of README.md; exit code 1 if the grammar is not LL(1).
+fn grammar() -> i32 {
+    print!("{}", markdown(sets()));
+    let conflicts = conflicts(PRODUCTIONS, sets());
+    for conflict in &conflicts {
+        eprintln!("error: {}", conflict);
+    }
+    return if conflicts.is_empty() { 0 } else { 1 };
+}
+
+/// `generate` subcommand: prints random lambdas, one per line, or near misses with `--mutate`.
+fn generate(args: &[String]) -> i32 {
+    let mut options = GeneratorOptions::default();
+    let mut count = 1;
+    let mut mutate = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--mutate" => mutate = true,
+            "--identifiers" => {
+                options.identifiers = match args.next() {
+                    Some(names) => names.split(',').filter(|s| !s.is_empty()).map(String::from).collect(),
+                    None => return usage_error("--identifiers expects a list of names"),
+                };
+                if options.identifiers.is_empty() {
+                    return usage_error("--identifiers expects a list of names");
+                }
+                if let Some(name) = options.identifiers.iter().find(|name| !is_identifier(name)) {
+                    return usage_error(&format!("--identifiers: `{}` is a keyword or not an identifier", name));
+                }
+            }
+            "--count" | "--seed" | "--max-depth" => {
+                let n = match args.next().map(|n| n.parse()) {
+                    Some(Ok(n)) => n,
+                    _ => return usage_error(&format!("{} expects a number", arg)),
+                };
+                match arg.as_str() {
+                    "--count" => count = n,
+                    "--seed" => options.seed = n,
+                    _ => options.max_depth = n as usize,
+                }
+            }
+            _ => return usage_error(&format!("unexpected argument {}", arg)),
+        }
+    }
+
+    let mut generator = Generator::new(options);
+    for _ in 0..count {
+        if mutate {
+            println!("{}", generator.mutant());
+        } else {
+            println!("{}", generator.lambda());
+        }
+    }
+    return 0;
+}
+
+/// Prints the long explanation of an error code such as `LP0002`.
+fn explain_code(code: Option<&String>) -> i32 {
+    let Some(code) = code else {
+        return usage_error("--explain expects an error code");
+    };
+    return match explain(code) {
+        Some(text) => {
+            print!("{}", text);
+            0
+        }
+        None => {
+            eprintln!("error: {} is not a valid error code", code);
+            1
+        }
+    };
+}
+
+fn usage_error(msg: &str) -> i32 {
+    eprintln!("error: {}\n{}", msg, USAGE);
+    return 2;
+}
+
+/// `fmt` subcommand. Exit code is 0 on success, 1 if `--check` found something to
+/// reformat, 2 on bad usage and 123 if some input could not be formatted, as in Black.
+fn fmt(args: &[String]) -> i32 {
+    let mut check = false;
+    let mut line_length = DEFAULT_LINE_LENGTH;
+    let mut code = None;
+    let mut files = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => check = true,
+            "-l" | "--line-length" => {
+                line_length = match args.next().map(|n| n.parse()) {
+                    Some(Ok(n)) => n,
+                    _ => return usage_error("--line-length expects a number"),
+                }
+            }
+            "-c" | "--code" => {
+                code = match args.next() {
+                    Some(c) => Some(c.clone()),
+                    None => return usage_error("--code expects an expression"),
+                }
+            }
+            _ => files.push(arg.clone()),
+        }
+    }
+
+    if let Some(code) = code {
+        return match format_lambda(&code, line_length) {
+            Ok(formatted) if check && formatted != code => {
+                eprintln!("would reformat -c");
+                1
+            }
+            Ok(_) if check => 0,
+            Ok(formatted) => {
+                println!("{}", formatted);
+                0
+            }
+            Err(e) => {
+                eprintln!("error: cannot format -c: {}", e);
+                123
+            }
+        };
+    }
+
+    if files.is_empty() {
+        files.push("-".to_string());
+    }
+
+    let mut status = 0;
+    for file in files {
+        let src = if file == "-" {
+            let mut buf = String::new();
+            io::stdin().read_to_string(&mut buf).map(|_| buf)
+        } else {
+            fs::read_to_string(&file)
+        };
+        let src = match src {
+            Ok(src) => src,
+            Err(e) => {
+                eprintln!("error: cannot read {}: {}", file, e);
+                status = 123;
+                continue;
+            }
+        };
+
+        let formatted = match format_source(&src, line_length) {
+            Ok(formatted) => formatted,
+            Err(e) => {
+                eprintln!("error: cannot format {}: {}", file, e);
+                status = 123;
+                continue;
+            }
+        };
+
+        if check {
+            if formatted != src {
+                eprintln!("would reformat {}", file);
+                status = status.max(1);
+            }
+        } else if file == "-" {
+            print!("{}", formatted);
+        } else if formatted != src {
+            if let Err(e) = fs::write(&file, formatted) {
+                eprintln!("error: cannot write {}: {}", file, e);
+                status = 123;
+                continue;
+            }
+            eprintln!("reformatted {}", file);
+        }
+    }
+    return status;
 }
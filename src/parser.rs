@@ -1,10 +1,48 @@
-pub(crate) mod parser {
+pub mod parser {
+    use std::collections::HashMap;
+
     use petgraph::Graph;
     use petgraph::graph::NodeIndex;
 
-    use crate::{Lexer, Token};
-    use crate::lexer::lexer::Constant::{False, Number, True};
-    use crate::lexer::lexer::Operations::{Add, And, Div, Eq, Mod, Mul, Not, Or, Sub};
+    use crate::grammar::grammar::{table, Symbol};
+    use crate::lexer::lexer::{Lexer, Token};
+    use crate::codes::codes;
+    use crate::diagnostic::diagnostic::{Applicability, Diagnostic};
+    use crate::lexer::lexer::{Span, TokenKind};
+    use crate::limits::limits::{LimitExceeded, ParseLimits};
+    use crate::lexer::lexer::Operations::{Add, Not, Sub};
+
+    pub use crate::grammar::grammar::NonTerminal;
+
+    /// Label of the node that replaces a subtree which could not be parsed.
+    pub const ERROR: &str = "<error>";
+
+    /// Tokens starting an operand; a set holding all of them is described as "expression".
+    const OPERAND: &[TokenKind] = &[
+        TokenKind::Variable, TokenKind::Number, TokenKind::True, TokenKind::False, TokenKind::LParen,
+    ];
+
+    /// `expression`, `` `:` ``, `` `,` or `:` ``, `` one of `or`, `)`, end of input ``.
+    fn describe(expected: &[TokenKind]) -> String {
+        let mut items: Vec<String> = Vec::new();
+        let is_expression = OPERAND.iter().all(|k| expected.contains(k));
+        if is_expression {
+            items.push("expression".to_string());
+        }
+        for k in expected {
+            if !is_expression || !NonTerminal::Sp.first().contains(k) {
+                items.push(k.to_string());
+            }
+        }
+        return match items.len() {
+            1 => items.remove(0),
+            2 => format!("{} or {}", items[0], items[1]),
+            _ => format!("one of {}", items.join(", ")),
+        };
+    }
+
+    /// Tokens panic mode recovery always stops at, whatever non-terminal failed.
+    pub(crate) const SYNC: &[Token] = &[Token::RParen, Token::Comma, Token::Colon, Token::End];
 
     #[derive(Debug, Eq, PartialEq, Clone)]
     pub struct Tree {
@@ -15,18 +53,299 @@ pub(crate) mod parser {
         pub fn get_leaf(graph: &mut Graph<String, &str>, tok: String) -> NodeIndex {
             return graph.add_node(tok);
         }
+
+        /// Children of `node` in the order they were derived (petgraph yields them reversed).
+        pub fn children(graph: &Graph<String, &str>, node: NodeIndex) -> Vec<NodeIndex> {
+            let mut children: Vec<NodeIndex> = graph.neighbors(node).collect();
+            children.reverse();
+            return children;
+        }
     }
 
     pub struct Parser<'a> {
-        pub(crate) lex: Lexer,
-        pub(crate) graph: Graph<String, &'a str>,
+        pub lex: Lexer<'a>,
+        pub graph: Graph<String, &'a str>,
+        pub diagnostics: Vec<Diagnostic>,
+        /// Where the token of every terminal leaf is in the source.
+        pub spans: HashMap<NodeIndex, Span>,
+        /// Set after an error until the next token is matched, so that one mistake
+        /// is not reported again by every enclosing non-terminal.
+        recovering: bool,
+        /// Parameters of the lambdas enclosing the current token.
+        params: Vec<&'a str>,
+        /// Number of `(` and `lambda` enclosing the current token.
+        depth: usize,
+    }
+
+    /// Constants of other languages and their Python spelling.
+    const FOREIGN_CONSTANTS: &[(&str, &str)] = &[("true", "True"), ("false", "False"), ("null", "None")];
+
+    fn found(tok: &Token) -> String {
+        return match tok {
+            Token::End => tok.to_string(),
+            _ => format!("`{}`", tok),
+        };
     }
 
-    impl Parser<'_> {
-        pub fn get(str: String) -> Parser<'static> {
-            return Parser { lex: Lexer::get(str), graph: Graph::new() };
+    /// `tok` at `span` where one of `expected` should be.
+    pub(crate) fn unexpected(tok: &Token, span: Span, expected: Vec<TokenKind>) -> Diagnostic {
+        let what = describe(&expected);
+        let label = if what.starts_with("one of") {
+            format!("expected one of {} possible tokens", expected.len())
+        } else {
+            format!("expected {}", what)
+        };
+        return Diagnostic::error(format!("expected {}, found {}", what, found(tok)), span)
+            .with_code(codes::UNEXPECTED_TOKEN)
+            .with_label(&label)
+            .with_expected(expected);
+    }
+
+    /// `tok` at `span` where the terminal `expected` should be. `opened_at` is the span
+    /// of the `(` that a `)` would close.
+    pub(crate) fn missing(tok: &Token, span: Span, expected: TokenKind, opened_at: Option<Span>) -> Diagnostic {
+        let mut e = unexpected(tok, span, vec![expected]);
+        if expected == TokenKind::Colon {
+            e = e.with_code(codes::MISSING_COLON);
         }
-        
+        if let Some(span) = opened_at {
+            e = e.with_code(codes::UNCLOSED_PARENTHESIS).with_secondary(span, "unclosed `(` opened here");
+        }
+        return e;
+    }
+
+    /// `tok` at `span` after the lambda, where the input should end.
+    pub(crate) fn trailing(tok: &Token, span: Span) -> Diagnostic {
+        return match tok {
+            Token::RParen => Diagnostic::error("unexpected closing delimiter: `)`".to_string(), span)
+                .with_code(codes::UNMATCHED_PARENTHESIS)
+                .with_label("unexpected closing delimiter")
+                .with_expected(vec![TokenKind::End]),
+            _ => unexpected(tok, span, vec![TokenKind::End]),
+        };
+    }
+
+    /// Warns about `true`, `false` and `null` at `span` unless they are among `params`:
+    /// as free variables they are valid, but most likely meant as constants.
+    pub(crate) fn foreign_constant(tok: &Token, span: Span, params: &[&str]) -> Option<Diagnostic> {
+        let Token::Variable(name) = tok else {
+            return None;
+        };
+        if params.contains(name) {
+            return None;
+        }
+        let (_, python) = FOREIGN_CONSTANTS.iter().find(|(c, _)| c == name)?;
+        return Some(Diagnostic::warning(format!("`{}` is not a Python constant", name), span)
+            .with_code(codes::FOREIGN_CONSTANT)
+            .with_label("not defined by the lambda")
+            .with_suggestion(&format!("did you mean `{}`?", python), span, python, Applicability::MaybeIncorrect));
+    }
+
+    // Methods are named after the non-terminals of the grammar in README.md.
+    #[allow(non_snake_case)]
+    impl<'a> Parser<'a> {
+        pub fn get(str: &'a str) -> Parser<'a> {
+            return Parser::with_limits(str, ParseLimits::default());
+        }
+
+        pub fn with_limits(str: &'a str, limits: ParseLimits) -> Parser<'a> {
+            return Parser {
+                lex: Lexer::with_limits(str, limits),
+                graph: Graph::new(),
+                diagnostics: Vec::new(),
+                spans: HashMap::new(),
+                recovering: false,
+                params: Vec::new(),
+                depth: 0,
+            };
+        }
+
+        /// Parses the whole input: a lambda followed by the end of input.
+        ///
+        /// Syntax errors do not stop the parse, they are collected in `diagnostics`
+        /// (ordered by position, along with warnings) and the tree gets error nodes
+        /// in their place.
+        pub fn parse(&mut self) -> NodeIndex {
+            let root = self.S();
+            return self.finish(root);
+        }
+
+        /// Reports and skips whatever follows the lambda at `root`.
+        fn finish(&mut self, root: NodeIndex) -> NodeIndex {
+            let tok = self.lex.get_token();
+            if tok != Token::End {
+                self.error(trailing(&tok, self.lex.get_span()));
+                let err = Tree::get_leaf(&mut self.graph, ERROR.to_string());
+                self.graph.add_edge(root, err, "");
+                self.skip(err, |tok| *tok == Token::End);
+            }
+
+            self.diagnostics.append(&mut self.lex.diagnostics);
+            self.diagnostics.sort_by_key(|e| e.span.start);
+            return root;
+        }
+
+        /// The limit that stopped parsing, if any.
+        pub fn exceeded(&self) -> Option<LimitExceeded> {
+            return self.lex.exceeded;
+        }
+
+        /// Errors after a limit was hit only say that the input ended early.
+        fn error(&mut self, e: Diagnostic) {
+            if !self.recovering && self.lex.exceeded.is_none() {
+                self.diagnostics.push(e);
+            }
+            self.recovering = true;
+        }
+
+        /// Matches the current token: adds it as the next leaf of `node` and moves past it.
+        fn shift(&mut self, node: NodeIndex) {
+            let xxx = Tree::get_leaf(&mut self.graph, self.lex.get_token().to_string());
+            self.graph.add_edge(node, xxx, "");
+            self.spans.insert(xxx, self.lex.get_span());
+            self.bump();
+        }
+
+        /// Moves past a token that matched the grammar.
+        fn bump(&mut self) {
+            self.recovering = false;
+            self.advance();
+        }
+
+        /// Moves to the next token, unless the tree has grown past its limit.
+        fn advance(&mut self) {
+            if self.graph.node_count() > self.lex.limits.max_nodes {
+                self.lex.stop(LimitExceeded::Nodes(self.lex.limits.max_nodes));
+                return;
+            }
+            self.lex.next_token();
+        }
+
+        /// Moves tokens under `err` until `stop` holds for the current one.
+        fn skip(&mut self, err: NodeIndex, stop: impl Fn(&Token) -> bool) {
+            loop {
+                let tok = self.lex.get_token();
+                if stop(&tok) {
+                    return;
+                }
+                let xxx = Tree::get_leaf(&mut self.graph, tok.to_string());
+                self.graph.add_edge(err, xxx, "");
+                self.spans.insert(xxx, self.lex.get_span());
+                self.advance();
+            }
+        }
+
+        /// Whether the current token predicts a production starting with `nt`.
+        fn starts(&self, nt: NonTerminal) -> bool {
+            return nt.first().contains(&self.lex.get_token().kind());
+        }
+
+        /// Whether the current token predicts the ε-production of `nt`.
+        fn follows(&self, nt: NonTerminal) -> bool {
+            return nt.follow().contains(&self.lex.get_token().kind());
+        }
+
+        fn unexpected(&self, expected: Vec<TokenKind>) -> Diagnostic {
+            return unexpected(&self.lex.get_token(), self.lex.get_span(), expected);
+        }
+
+        /// Panic mode: reports the current token and skips until a token that may follow
+        /// `nt` or one of `SYNC`, leaving an error node under `node`.
+        fn recover(&mut self, node: NodeIndex, nt: NonTerminal) {
+            let code = match nt {
+                NonTerminal::S => codes::MISSING_LAMBDA,
+                NonTerminal::V | NonTerminal::Vp => codes::INVALID_PARAMETERS,
+                _ => codes::UNEXPECTED_TOKEN,
+            };
+            let e = self.unexpected(nt.expected()).with_code(code);
+            self.error(e);
+            let err = Tree::get_leaf(&mut self.graph, ERROR.to_string());
+            self.graph.add_edge(node, err, "");
+            self.skip(err, |tok| nt.follow().contains(&tok.kind()) || SYNC.contains(tok));
+        }
+
+        /// Matches the terminal `expected`. If something else is found, it is reported
+        /// and skipped up to the next `SYNC` token, which is consumed if it is `expected`.
+        /// `opened_at` is the span of the `(` that a `)` would close.
+        fn expect(&mut self, node: NodeIndex, expected: TokenKind, opened_at: Option<Span>) {
+            let tok = self.lex.get_token();
+            if tok.kind() != expected {
+                self.error(missing(&tok, self.lex.get_span(), expected, opened_at));
+                let err = Tree::get_leaf(&mut self.graph, ERROR.to_string());
+                self.graph.add_edge(node, err, "");
+                self.skip(err, |tok| SYNC.contains(tok));
+                if self.lex.get_token().kind() != expected {
+                    return;
+                }
+            }
+            self.shift(node);
+        }
+
+        fn check_foreign_constant(&mut self, tok: Token<'a>) {
+            if let Some(w) = foreign_constant(&tok, self.lex.get_span(), &self.params) {
+                self.diagnostics.push(w);
+            }
+        }
+
+        /// Enters a `(` or a `lambda`. Past the depth limit parsing stops there and
+        /// `node` gets an error node instead.
+        fn enter(&mut self, node: NodeIndex) -> bool {
+            if self.depth < self.lex.limits.max_depth {
+                self.depth += 1;
+                return true;
+            }
+            self.lex.stop(LimitExceeded::Depth(self.lex.limits.max_depth));
+            let err = Tree::get_leaf(&mut self.graph, ERROR.to_string());
+            self.graph.add_edge(node, err, "");
+            return false;
+        }
+
+        /// `nt → operand tail`, `tail → op nt | ε`: a chain of operators of the same
+        /// precedence. It is parsed in a loop, so that a long chain does not grow the
+        /// stack, but gives the same tree as the recursive derivation.
+        fn chain(&mut self, nt: NonTerminal, operand: fn(&mut Self) -> NodeIndex, tail: NonTerminal) -> NodeIndex {
+            let root = Tree::get_leaf(&mut self.graph, nt.label().to_string());
+            let mut node = root;
+            // Edges to the nested nodes, added innermost first when the chain ends, like
+            // recursion would add them.
+            let mut nested = Vec::new();
+            loop {
+                if !self.starts(nt) {
+                    self.recover(node, nt);
+                    break;
+                }
+                // operand
+                let xxx = operand(self);
+                self.graph.add_edge(node, xxx, "");
+                // tail
+                let tail_node = Tree::get_leaf(&mut self.graph, tail.label().to_string());
+                nested.push((node, tail_node));
+
+                let tok = self.lex.get_token();
+                match tok.kind() {
+                    _ if self.starts(tail) => {
+                        // op
+                        self.shift(tail_node);
+                        // nt
+                        node = Tree::get_leaf(&mut self.graph, nt.label().to_string());
+                        nested.push((tail_node, node));
+                    }
+                    _ if self.follows(tail) => {
+                        self.add_eps_node(tail_node);
+                        break;
+                    }
+                    _ => {
+                        self.recover(tail_node, tail);
+                        break;
+                    }
+                }
+            }
+            for (parent, child) in nested.into_iter().rev() {
+                self.graph.add_edge(parent, child, "");
+            }
+            return root;
+        }
+
         fn add_eps_node(&mut self, p: NodeIndex) {
             let xxx = Tree::get_leaf(&mut self.graph, "ε".to_string());
             self.graph.add_edge(p, xxx, "");
@@ -35,25 +354,27 @@ pub(crate) mod parser {
         pub fn S(&mut self) -> NodeIndex {
             let node = Tree::get_leaf(&mut self.graph, "S".to_string());
 
-            match self.lex.get_token() {
-                Token::Lambda => {
+            let tok = self.lex.get_token();
+            match tok.kind() {
+                TokenKind::Lambda => {
+                    if !self.enter(node) {
+                        return node;
+                    }
                     // lambda
-                    self.lex.next_token();
-                    let xxx = Tree::get_leaf(&mut self.graph, "lambda".to_string());
-                    self.graph.add_edge(node, xxx, "");
+                    let scope = self.params.len();
+                    self.shift(node);
                     // V
                     let xxx = self.V();
                     self.graph.add_edge(node, xxx, "");
                     // :
-                    assert_eq!(self.lex.get_token(), Token::Colon);
-                    self.lex.next_token();
-                    let xxx = Tree::get_leaf(&mut self.graph, ":".to_string());
-                    self.graph.add_edge(node, xxx, "");
+                    self.expect(node, TokenKind::Colon, None);
                     // S'
                     let xxx = self.Sp();
                     self.graph.add_edge(node, xxx, "");
+                    self.params.truncate(scope);
+                    self.depth -= 1;
                 }
-                tok => panic!("Unexpected token {:?}", tok)
+                _ => self.recover(node, NonTerminal::S)
             }
 
             return node;
@@ -62,18 +383,18 @@ pub(crate) mod parser {
         pub fn V(&mut self) -> NodeIndex {
             let node = Tree::get_leaf(&mut self.graph, "V".to_string());
 
-            match self.lex.get_token() {
-                Token::Variable(str) => {
+            let tok = self.lex.get_token();
+            match tok {
+                Token::Variable(name) => {
                     // var
-                    self.lex.next_token();
-                    let xxx = Tree::get_leaf(&mut self.graph, str);
-                    self.graph.add_edge(node, xxx, "");
+                    self.params.push(name);
+                    self.shift(node);
                     // V'
                     let xxx = self.Vp();
                     self.graph.add_edge(node, xxx, "");
                 }
-                Token::Colon => {self.add_eps_node(node)}
-                tok => panic!("Unexpected token {:?}", tok)
+                _ if self.follows(NonTerminal::V) => {self.add_eps_node(node)}
+                _ => self.recover(node, NonTerminal::V)
             }
 
             return node;
@@ -82,18 +403,17 @@ pub(crate) mod parser {
         pub fn Vp(&mut self) -> NodeIndex {
             let node = Tree::get_leaf(&mut self.graph, "V'".to_string());
 
-            match self.lex.get_token() {
-                Token::Comma => {
+            let tok = self.lex.get_token();
+            match tok.kind() {
+                TokenKind::Comma => {
                     // var
-                    self.lex.next_token();
-                    let xxx = Tree::get_leaf(&mut self.graph, ",".to_string());
-                    self.graph.add_edge(node, xxx, "");
+                    self.shift(node);
                     // V'
                     let xxx = self.V();
                     self.graph.add_edge(node, xxx, "");
                 }
-                Token::Colon => {self.add_eps_node(node)}
-                tok => panic!("Unexpected token {:?}", tok)
+                _ if self.follows(NonTerminal::Vp) => {self.add_eps_node(node)}
+                _ => self.recover(node, NonTerminal::Vp)
             }
 
             return node;
@@ -102,339 +422,96 @@ pub(crate) mod parser {
         pub fn Sp(&mut self) -> NodeIndex {
             let node = Tree::get_leaf(&mut self.graph, "S'".to_string());
 
-            match self.lex.get_token() {
-                Token::Lambda => {
+            let tok = self.lex.get_token();
+            match tok.kind() {
+                _ if self.starts(NonTerminal::S) => {
                     // S
                     let xxx = self.S();
                     self.graph.add_edge(node, xxx, "");
                 }
-                Token::Variable(_)
-                | Token::Op(Not)
-                | Token::Op(Add)
-                | Token::Op(Sub)
-                | Token::Const(_)
-                | Token::LParen => {
+                _ if self.starts(NonTerminal::E) => {
                     let xxx = self.E();
                     self.graph.add_edge(node, xxx, "");
                 }
-                tok => panic!("Unexpected token {:?}", tok)
+                _ => self.recover(node, NonTerminal::Sp)
             }
 
             return node;
         }
 
         pub fn E(&mut self) -> NodeIndex {
-            let node = Tree::get_leaf(&mut self.graph, "E".to_string());
-
-            match self.lex.get_token() {
-                Token::Variable(_)
-                | Token::Op(Not)
-                | Token::Op(Add)
-                | Token::Op(Sub)
-                | Token::Const(_)
-                | Token::LParen => {
-                    // O
-                    let xxx = self.O();
-                    self.graph.add_edge(node, xxx, "");
-                    // E'
-                    let xxx = self.Ep();
-                    self.graph.add_edge(node, xxx, "");
-                }
-                tok => panic!("Unexpected token {:?}", tok)
-            }
-
-            return node;
-        }
-
-        pub fn Ep(&mut self) -> NodeIndex {
-            let node = Tree::get_leaf(&mut self.graph, "Ep".to_string());
-
-            match self.lex.get_token() {
-                Token::Op(Or) => {
-                    // or
-                    self.lex.next_token();
-                    let xxx = Tree::get_leaf(&mut self.graph, "or".to_string());
-                    self.graph.add_edge(node, xxx, "");
-                    // E
-                    let xxx = self.E();
-                    self.graph.add_edge(node, xxx, "");
-                }
-                Token::RParen | Token::End => {self.add_eps_node(node)}
-                tok => panic!("Unexpected token {:?}", tok)
-            }
-
-            return node;
+            // O Ep
+            return self.chain(NonTerminal::E, Parser::O, NonTerminal::Ep);
         }
 
         pub fn O(&mut self) -> NodeIndex {
-            let node = Tree::get_leaf(&mut self.graph, "O".to_string());
-
-            match self.lex.get_token() {
-                Token::Variable(_)
-                | Token::Op(Not)
-                | Token::Op(Add)
-                | Token::Op(Sub)
-                | Token::Const(_)
-                | Token::LParen => {
-                    // A
-                    let xxx = self.A();
-                    self.graph.add_edge(node, xxx, "");
-                    // O'
-                    let xxx = self.Op();
-                    self.graph.add_edge(node, xxx, "");
-                }
-                tok => panic!("Unexpected token {:?}", tok)
-            }
-
-            return node;
-        }
-
-        pub fn Op(&mut self) -> NodeIndex {
-            let node = Tree::get_leaf(&mut self.graph, "Op".to_string());
-
-            match self.lex.get_token() {
-                Token::Op(And) => {
-                    // and
-                    self.lex.next_token();
-                    let xxx = Tree::get_leaf(&mut self.graph, "and".to_string());
-                    self.graph.add_edge(node, xxx, "");
-                    // O
-                    let xxx = self.O();
-                    self.graph.add_edge(node, xxx, "");
-                }
-                Token::RParen
-                | Token::End
-                | Token::Op(Or) => {self.add_eps_node(node)}
-                tok => panic!("Unexpected token {:?}", tok)
-            }
-
-            return node;
+            // A Op
+            return self.chain(NonTerminal::O, Parser::A, NonTerminal::Op);
         }
 
         pub fn A(&mut self) -> NodeIndex {
             let node = Tree::get_leaf(&mut self.graph, "A".to_string());
 
-            match self.lex.get_token() {
-                Token::Op(Not) => {
+            let tok = self.lex.get_token();
+            match tok.kind() {
+                TokenKind::Op(Not) => {
                     // not
-                    self.lex.next_token();
-                    let xxx = Tree::get_leaf(&mut self.graph, "not".to_string());
-                    self.graph.add_edge(node, xxx, "");
+                    self.shift(node);
                     // N
                     let xxx = self.N();
                     self.graph.add_edge(node, xxx, "");
                 }
-                Token::Variable(_)
-                | Token::Op(Add)
-                | Token::Op(Sub)
-                | Token::Const(_)
-                | Token::LParen => {
+                _ if self.starts(NonTerminal::N) => {
                     // N
                     let xxx = self.N();
                     self.graph.add_edge(node, xxx, "");
                 }
-                tok => panic!("Unexpected token {:?}", tok)
+                _ => self.recover(node, NonTerminal::A)
             }
 
             return node;
         }
 
         pub fn N(&mut self) -> NodeIndex {
-            let node = Tree::get_leaf(&mut self.graph, "N".to_string());
-
-            match self.lex.get_token() {
-                Token::Variable(_)
-                | Token::Op(Add)
-                | Token::Op(Sub)
-                | Token::Const(_)
-                | Token::LParen => {
-                    // Q
-                    let xxx = self.Q();
-                    self.graph.add_edge(node, xxx, "");
-                    // N'
-                    let xxx = self.Np();
-                    self.graph.add_edge(node, xxx, "");
-                }
-                tok => panic!("Unexpected token {:?}", tok)
-            }
-
-            return node;
-        }
-
-        pub fn Np(&mut self) -> NodeIndex {
-            let node = Tree::get_leaf(&mut self.graph, "Np".to_string());
-
-            match self.lex.get_token() {
-                Token::Op(Eq) => {
-                    // ==
-                    self.lex.next_token();
-                    let xxx = Tree::get_leaf(&mut self.graph, "==".to_string());
-                    self.graph.add_edge(node, xxx, "");
-                    // N
-                    let xxx = self.N();
-                    self.graph.add_edge(node, xxx, "");
-                }
-                Token::RParen
-                | Token::End
-                | Token::Op(Or)
-                | Token::Op(And) => {self.add_eps_node(node)}
-                tok => panic!("Unexpected token {:?}", tok)
-            }
-
-            return node;
+            // Q Np
+            return self.chain(NonTerminal::N, Parser::Q, NonTerminal::Np);
         }
 
         pub fn Q(&mut self) -> NodeIndex {
-            let node = Tree::get_leaf(&mut self.graph, "Q".to_string());
-
-            match self.lex.get_token() {
-                Token::Variable(_)
-                | Token::Op(Add)
-                | Token::Op(Sub)
-                | Token::Const(_)
-                | Token::LParen => {
-                    // T
-                    let xxx = self.T();
-                    self.graph.add_edge(node, xxx, "");
-                    // Q'
-                    let xxx = self.Qp();
-                    self.graph.add_edge(node, xxx, "");
-                }
-                tok => panic!("Unexpected token {:?}", tok)
-            }
-
-            return node;
-        }
-
-        pub fn Qp(&mut self) -> NodeIndex {
-            let node = Tree::get_leaf(&mut self.graph, "Qp".to_string());
-
-            match self.lex.get_token() {
-                Token::Op(Add) => {
-                    // +
-                    self.lex.next_token();
-                    let xxx = Tree::get_leaf(&mut self.graph, "+".to_string());
-                    self.graph.add_edge(node, xxx, "");
-                    // Q
-                    let xxx = self.Q();
-                    self.graph.add_edge(node, xxx, "");
-                }
-                Token::Op(Sub) => {
-                    // -
-                    self.lex.next_token();
-                    let xxx = Tree::get_leaf(&mut self.graph, "-".to_string());
-                    self.graph.add_edge(node, xxx, "");
-                    // Q
-                    let xxx = self.Q();
-                    self.graph.add_edge(node, xxx, "");
-                }
-                Token::RParen
-                | Token::End
-                | Token::Op(Or)
-                | Token::Op(And)
-                | Token::Op(Eq) => {self.add_eps_node(node)}
-                tok => panic!("Unexpected token {:?}", tok)
-            }
-
-            return node;
+            // T Qp
+            return self.chain(NonTerminal::Q, Parser::T, NonTerminal::Qp);
         }
 
         pub fn T(&mut self) -> NodeIndex {
-            let node = Tree::get_leaf(&mut self.graph, "T".to_string());
-
-            match self.lex.get_token() {
-                Token::Variable(_)
-                | Token::Op(Add)
-                | Token::Op(Sub)
-                | Token::Const(_)
-                | Token::LParen => {
-                    // F
-                    let xxx = self.F();
-                    self.graph.add_edge(node, xxx, "");
-                    // T'
-                    let xxx = self.Tp();
-                    self.graph.add_edge(node, xxx, "");
-                }
-                tok => panic!("Unexpected token {:?}", tok)
-            }
-
-            return node;
-        }
-
-        pub fn Tp(&mut self) -> NodeIndex {
-            let node = Tree::get_leaf(&mut self.graph, "Tp".to_string());
-
-            match self.lex.get_token() {
-                Token::Op(Mul) => {
-                    // *
-                    self.lex.next_token();
-                    let xxx = Tree::get_leaf(&mut self.graph, "*".to_string());
-                    self.graph.add_edge(node, xxx, "");
-                    // T
-                    let xxx = self.T();
-                    self.graph.add_edge(node, xxx, "");
-                }
-                Token::Op(Div) => {
-                    // //
-                    self.lex.next_token();
-                    let xxx = Tree::get_leaf(&mut self.graph, "//".to_string());
-                    self.graph.add_edge(node, xxx, "");
-                    // T
-                    let xxx = self.T();
-                    self.graph.add_edge(node, xxx, "");
-                }
-                Token::Op(Mod) => {
-                    // %
-                    self.lex.next_token();
-                    let xxx = Tree::get_leaf(&mut self.graph, "%".to_string());
-                    self.graph.add_edge(node, xxx, "");
-                    // T
-                    let xxx = self.T();
-                    self.graph.add_edge(node, xxx, "");
-                }
-                Token::RParen
-                | Token::End
-                | Token::Op(Or)
-                | Token::Op(And)
-                | Token::Op(Eq)
-                | Token::Op(Add)
-                | Token::Op(Sub) => {self.add_eps_node(node)}
-                tok => panic!("Unexpected token {:?}", tok)
-            }
-
-            return node;
+            // F Tp
+            return self.chain(NonTerminal::T, Parser::F, NonTerminal::Tp);
         }
 
         pub fn F(&mut self) -> NodeIndex {
             let node = Tree::get_leaf(&mut self.graph, "F".to_string());
 
-            match self.lex.get_token() {
-                Token::Op(Add) => {
+            let tok = self.lex.get_token();
+            match tok.kind() {
+                TokenKind::Op(Add) => {
                     // +
-                    self.lex.next_token();
-                    let xxx = Tree::get_leaf(&mut self.graph, "+".to_string());
-                    self.graph.add_edge(node, xxx, "");
+                    self.shift(node);
                     // C
                     let xxx = self.C();
                     self.graph.add_edge(node, xxx, "");
                 }
-                Token::Op(Sub) => {
+                TokenKind::Op(Sub) => {
                     // -
-                    self.lex.next_token();
-                    let xxx = Tree::get_leaf(&mut self.graph, "-".to_string());
-                    self.graph.add_edge(node, xxx, "");
+                    self.shift(node);
                     // C
                     let xxx = self.C();
                     self.graph.add_edge(node, xxx, "");
                 }
-                Token::Variable(_)
-                | Token::Const(_)
-                | Token::LParen => {
+                _ if self.starts(NonTerminal::C) => {
                     // C
                     let xxx = self.C();
                     self.graph.add_edge(node, xxx, "");
                 }
-                tok => panic!("Unexpected token {:?}", tok)
+                _ => self.recover(node, NonTerminal::F)
             }
 
             return node;
@@ -443,41 +520,112 @@ pub(crate) mod parser {
         pub fn C(&mut self) -> NodeIndex {
             let node = Tree::get_leaf(&mut self.graph, "C".to_string());
 
-            match self.lex.get_token() {
-                Token::Variable(str)
-                | Token::Const(Number(str)) => {
-                    self.lex.next_token();
-                    let xxx = Tree::get_leaf(&mut self.graph, str);
-                    self.graph.add_edge(node, xxx, "");
-                }
-                Token::Const(True) => {
-                    self.lex.next_token();
-                    let xxx = Tree::get_leaf(&mut self.graph, "True".to_string());
-                    self.graph.add_edge(node, xxx, "");
+            let tok = self.lex.get_token();
+            match tok.kind() {
+                TokenKind::Variable
+                | TokenKind::Number
+                | TokenKind::True
+                | TokenKind::False => {
+                    self.check_foreign_constant(tok);
+                    self.shift(node);
                 }
-                Token::Const(False) => {
-                    self.lex.next_token();
-                    let xxx = Tree::get_leaf(&mut self.graph, "False".to_string());
-                    self.graph.add_edge(node, xxx, "");
-                }
-                Token::LParen => {
+                TokenKind::LParen => {
+                    if !self.enter(node) {
+                        return node;
+                    }
                     // (
-                    self.lex.next_token();
-                    let xxx = Tree::get_leaf(&mut self.graph, "(".to_string());
-                    self.graph.add_edge(node, xxx, "");
+                    let open = self.lex.get_span();
+                    self.shift(node);
                     // S'
                     let xxx = self.Sp();
                     self.graph.add_edge(node, xxx, "");
                     // )
-                    assert_eq!(self.lex.get_token(), Token::RParen);
-                    self.lex.next_token();
-                    let xxx = Tree::get_leaf(&mut self.graph, ")".to_string());
-                    self.graph.add_edge(node, xxx, "");
+                    self.expect(node, TokenKind::RParen, Some(open));
+                    self.depth -= 1;
                 }
-                tok => panic!("Unexpected token {:?}", tok)
+                _ => self.recover(node, NonTerminal::C)
             }
 
             return node;
         }
     }
+
+    /// What is left to do on the stack of the table-driven parser.
+    enum Step {
+        /// Predicts a production for `nt` and puts its node under `parent`.
+        Expand { nt: NonTerminal, parent: Option<NodeIndex> },
+        /// Matches a terminal of the production of `nt` at `node`.
+        Match { kind: TokenKind, nt: NonTerminal, node: NodeIndex },
+        /// `node` is complete: it goes under `parent`, after its children as recursion
+        /// would add it. `scope` is set when `node` opened a `(` or a `lambda`.
+        Close { node: NodeIndex, parent: Option<NodeIndex>, scope: Option<usize> },
+    }
+
+    /// Table-driven parsing: the productions and prediction table of `grammar` and
+    /// an explicit stack stand for the methods above.
+    impl<'a> Parser<'a> {
+        /// Parses the whole input like `parse`, with the generic LL(1) driver: the
+        /// tree, its node and edge order, and the diagnostics are the same.
+        pub fn parse_table(&mut self) -> NodeIndex {
+            let mut root = None;
+            // Spans of the `(` that the expected `)` close.
+            let mut open = Vec::new();
+            let mut stack = vec![Step::Expand { nt: NonTerminal::S, parent: None }];
+            while let Some(step) = stack.pop() {
+                match step {
+                    Step::Expand { nt, parent } => {
+                        let node = Tree::get_leaf(&mut self.graph, nt.label().to_string());
+                        root = root.or(Some(node));
+                        let Some(p) = table().predict(nt, self.lex.get_token().kind()) else {
+                            self.recover(node, nt);
+                            stack.push(Step::Close { node, parent, scope: None });
+                            continue;
+                        };
+                        let mut scope = None;
+                        if matches!(p.rhs.first(), Some(Symbol::T(TokenKind::Lambda | TokenKind::LParen))) {
+                            if !self.enter(node) {
+                                stack.push(Step::Close { node, parent, scope: None });
+                                continue;
+                            }
+                            scope = Some(self.params.len());
+                        }
+                        if p.rhs.is_empty() {
+                            self.add_eps_node(node);
+                        }
+                        stack.push(Step::Close { node, parent, scope });
+                        for symbol in p.rhs.iter().rev() {
+                            stack.push(match *symbol {
+                                Symbol::T(kind) => Step::Match { kind, nt, node },
+                                Symbol::N(child) => Step::Expand { nt: child, parent: Some(node) },
+                            });
+                        }
+                    }
+                    Step::Match { kind, nt, node } => {
+                        let tok = self.lex.get_token();
+                        let opened_at = if kind == TokenKind::RParen { open.pop() } else { None };
+                        if tok.kind() == kind {
+                            match tok {
+                                Token::Variable(name) if nt == NonTerminal::V => self.params.push(name),
+                                Token::LParen => open.push(self.lex.get_span()),
+                                _ if nt == NonTerminal::C => self.check_foreign_constant(tok),
+                                _ => {}
+                            }
+                        }
+                        self.expect(node, kind, opened_at);
+                    }
+                    Step::Close { node, parent, scope } => {
+                        if let Some(parent) = parent {
+                            self.graph.add_edge(parent, node, "");
+                        }
+                        if let Some(scope) = scope {
+                            self.params.truncate(scope);
+                            self.depth -= 1;
+                        }
+                    }
+                }
+            }
+            let root = root.expect("the start symbol is expanded first");
+            return self.finish(root);
+        }
+    }
 }
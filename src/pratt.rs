@@ -0,0 +1,371 @@
+pub mod pratt {
+    //! Operator-precedence (Pratt) parsing straight to an `Ast`, without a derivation
+    //! tree. Precedence lives in the `INFIX` and `PREFIX` tables instead of a pair of
+    //! non-terminals per level, and the tree is the one `lower` gives for the same
+    //! source, so either path can check the other.
+
+    use crate::ast::ast::{Ast, Node, NodeId, Symbol};
+    use crate::codes::codes;
+    use crate::diagnostic::diagnostic::Diagnostic;
+    use crate::lexer::lexer::{Constant, Lexer, Operations, Span, Token, TokenKind};
+    use crate::lexer::lexer::Operations::{Add, And, Div, Eq, Mod, Mul, Not, Or, Sub};
+    use crate::limits::limits::{LimitExceeded, ParseLimits};
+    use crate::parser::parser::{foreign_constant, missing, trailing, unexpected, SYNC};
+
+    #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+    pub enum Associativity {
+        /// `a - b - c` is `(a - b) - c`.
+        Left,
+        /// `a or b or c` is one node of three operands, as `BoolOp` and `Compare` are.
+        Flat,
+    }
+
+    /// A binary operator; the higher its `power`, the tighter it binds.
+    #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+    pub struct Infix {
+        pub op: Operations,
+        pub power: u8,
+        pub associativity: Associativity,
+    }
+
+    /// A unary operator. It may start an operand where operators looser than `power`
+    /// are parsed, and its own operand holds only operators tighter than `operand`:
+    /// `not` takes `a == b` but not `not b`, `-` takes only an atom.
+    #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+    pub struct Prefix {
+        pub op: Operations,
+        pub power: u8,
+        pub operand: u8,
+    }
+
+    /// Binding power where a lambda may start: a body or a parenthesized expression.
+    const LAMBDA: u8 = 0;
+
+    /// Binary operators from the loosest to the tightest.
+    pub const INFIX: &[Infix] = &[
+        Infix { op: Or, power: 1, associativity: Associativity::Flat },
+        Infix { op: And, power: 2, associativity: Associativity::Flat },
+        Infix { op: Eq, power: 4, associativity: Associativity::Flat },
+        Infix { op: Add, power: 5, associativity: Associativity::Left },
+        Infix { op: Sub, power: 5, associativity: Associativity::Left },
+        Infix { op: Mul, power: 6, associativity: Associativity::Left },
+        Infix { op: Div, power: 6, associativity: Associativity::Left },
+        Infix { op: Mod, power: 6, associativity: Associativity::Left },
+    ];
+
+    pub const PREFIX: &[Prefix] = &[
+        Prefix { op: Not, power: 3, operand: 3 },
+        Prefix { op: Add, power: 7, operand: 7 },
+        Prefix { op: Sub, power: 7, operand: 7 },
+    ];
+
+    fn infix(tok: &Token) -> Option<Infix> {
+        return match tok {
+            Token::Op(op) => INFIX.iter().find(|i| i.op == *op).copied(),
+            _ => None,
+        };
+    }
+
+    fn tightest() -> u8 {
+        return INFIX.iter().map(|i| i.power).max().unwrap_or(0);
+    }
+
+    /// Tokens that may follow an operand: any binary operator, `)` or the end. They
+    /// are listed as `Parser` does, the tightest operators first.
+    fn follow() -> Vec<TokenKind> {
+        let first = INFIX.iter().filter(|i| i.power == tightest());
+        let others = INFIX.iter().filter(|i| i.power != tightest());
+        let mut follow: Vec<TokenKind> = first.chain(others).map(|i| TokenKind::Op(i.op)).collect();
+        follow.extend([TokenKind::RParen, TokenKind::End]);
+        return follow;
+    }
+
+    pub struct PrattParser<'a> {
+        pub lex: Lexer<'a>,
+        pub ast: Ast,
+        pub diagnostics: Vec<Diagnostic>,
+        /// Set after an error until the next token is matched, see `Parser`.
+        recovering: bool,
+        /// Parameters of the lambdas enclosing the current token.
+        params: Vec<&'a str>,
+        /// Number of `(` and `lambda` enclosing the current token.
+        depth: usize,
+        /// End of the last token moved past, where an empty error node is put.
+        last_end: usize,
+    }
+
+    impl<'a> PrattParser<'a> {
+        pub fn get(str: &'a str) -> PrattParser<'a> {
+            return PrattParser::with_limits(str, ParseLimits::default());
+        }
+
+        pub fn with_limits(str: &'a str, limits: ParseLimits) -> PrattParser<'a> {
+            return PrattParser {
+                lex: Lexer::with_limits(str, limits),
+                ast: Ast::new(),
+                diagnostics: Vec::new(),
+                recovering: false,
+                params: Vec::new(),
+                depth: 0,
+                last_end: 0,
+            };
+        }
+
+        /// Parses the whole input into `ast` and returns its root. Errors are
+        /// collected in `diagnostics` and reported the way `Parser::parse` does.
+        pub fn parse(&mut self) -> NodeId {
+            let root = match self.lex.get_token() {
+                Token::Lambda => self.lambda().0,
+                tok => {
+                    let e = unexpected(&tok, self.lex.get_span(), vec![TokenKind::Lambda]).with_code(codes::MISSING_LAMBDA);
+                    self.error(e);
+                    // Nothing after it can be matched: the whole input is the error.
+                    self.skip(|tok| *tok == Token::End).0
+                }
+            };
+            let tok = self.lex.get_token();
+            if tok != Token::End {
+                self.error(trailing(&tok, self.lex.get_span()));
+                while self.lex.get_token() != Token::End {
+                    self.advance();
+                }
+            }
+            self.ast.set_root(root);
+
+            self.diagnostics.append(&mut self.lex.diagnostics);
+            self.diagnostics.sort_by_key(|e| e.span.start);
+            return root;
+        }
+
+        /// The limit that stopped parsing, if any.
+        pub fn exceeded(&self) -> Option<LimitExceeded> {
+            return self.lex.exceeded;
+        }
+
+        fn error(&mut self, e: Diagnostic) {
+            if !self.recovering && self.lex.exceeded.is_none() {
+                self.diagnostics.push(e);
+            }
+            self.recovering = true;
+        }
+
+        fn bump(&mut self) {
+            self.recovering = false;
+            self.advance();
+        }
+
+        fn advance(&mut self) {
+            self.last_end = self.lex.get_span().end;
+            if self.ast.len() > self.lex.limits.max_nodes {
+                self.lex.stop(LimitExceeded::Nodes(self.lex.limits.max_nodes));
+                return;
+            }
+            self.lex.next_token();
+        }
+
+        /// Moves past tokens until `stop` holds and adds an error node covering them.
+        fn skip(&mut self, stop: impl Fn(&Token) -> bool) -> (NodeId, Span) {
+            let mut span = Span { start: self.last_end, end: self.last_end };
+            let mut first = true;
+            while !stop(&self.lex.get_token()) {
+                if first {
+                    span.start = self.lex.get_span().start;
+                    first = false;
+                }
+                self.advance();
+                span.end = self.last_end;
+            }
+            return (self.ast.add(Node::Error, span), span);
+        }
+
+        fn expect(&mut self, expected: Token, opened_at: Option<Span>) {
+            let tok = self.lex.get_token();
+            if tok != expected {
+                self.error(missing(&tok, self.lex.get_span(), expected.kind(), opened_at));
+                while !SYNC.contains(&self.lex.get_token()) {
+                    self.advance();
+                }
+                if self.lex.get_token() != expected {
+                    return;
+                }
+            }
+            self.bump();
+        }
+
+        /// Enters a `(` or a `lambda`, unless that is past the depth limit.
+        fn enter(&mut self) -> bool {
+            if self.depth < self.lex.limits.max_depth {
+                self.depth += 1;
+                return true;
+            }
+            self.lex.stop(LimitExceeded::Depth(self.lex.limits.max_depth));
+            return false;
+        }
+
+        /// `lambda params: body`, the current token being `lambda`.
+        fn lambda(&mut self) -> (NodeId, Span) {
+            let start = self.lex.get_span().start;
+            if !self.enter() {
+                return self.skip(|_| true);
+            }
+            let scope = self.params.len();
+            self.bump();
+            let params = self.params();
+            self.expect(Token::Colon, None);
+            let (body, outer) = self.expression(LAMBDA);
+            self.params.truncate(scope);
+            self.depth -= 1;
+
+            let params = self.ast.param_list(&params);
+            let span = Span { start, end: outer.end };
+            return (self.ast.add(Node::Lambda { params, body }, span), span);
+        }
+
+        /// Names separated by commas, a trailing one allowed, up to the `:`.
+        fn params(&mut self) -> Vec<Symbol> {
+            let mut params = Vec::new();
+            loop {
+                match self.lex.get_token() {
+                    Token::Variable(name) => {
+                        self.params.push(name);
+                        params.push(self.ast.intern(name));
+                        self.bump();
+                    }
+                    Token::Colon => return params,
+                    _ => {
+                        self.invalid_params(vec![TokenKind::Variable, TokenKind::Colon]);
+                        return params;
+                    }
+                }
+                match self.lex.get_token() {
+                    Token::Comma => self.bump(),
+                    Token::Colon => return params,
+                    _ => {
+                        self.invalid_params(vec![TokenKind::Comma, TokenKind::Colon]);
+                        return params;
+                    }
+                }
+            }
+        }
+
+        fn invalid_params(&mut self, expected: Vec<TokenKind>) {
+            let e = unexpected(&self.lex.get_token(), self.lex.get_span(), expected).with_code(codes::INVALID_PARAMETERS);
+            self.error(e);
+            while !SYNC.contains(&self.lex.get_token()) {
+                self.advance();
+            }
+        }
+
+        /// An expression of operators binding tighter than `min`, along with the span
+        /// of its tokens, parentheses included. Operators of the same power are gathered
+        /// in a loop, so a long chain of them does not grow the stack.
+        fn expression(&mut self, min: u8) -> (NodeId, Span) {
+            let (mut left, mut outer) = self.operand(min);
+            loop {
+                let tok = self.lex.get_token();
+                let Some(infix) = infix(&tok) else {
+                    if tok != Token::RParen && tok != Token::End {
+                        // Skipped up to an operator looser than the tightest ones, as the
+                        // innermost tail of `Parser` would.
+                        self.error(unexpected(&tok, self.lex.get_span(), follow()));
+                        let stop = |tok: &Token| SYNC.contains(tok) || infix(tok).is_some_and(|i| i.power < tightest());
+                        while !stop(&self.lex.get_token()) {
+                            self.advance();
+                        }
+                        if infix(&self.lex.get_token()).is_some() {
+                            continue;
+                        }
+                    }
+                    return (left, outer);
+                };
+                if infix.power <= min {
+                    return (left, outer);
+                }
+
+                let mut operands = vec![left];
+                loop {
+                    self.bump();
+                    let (right, span) = self.expression(infix.power);
+                    operands.push(right);
+                    outer.end = span.end;
+                    if infix.associativity != Associativity::Flat || self.lex.get_token() != Token::Op(infix.op) {
+                        break;
+                    }
+                }
+                let node = match (infix.associativity, infix.op) {
+                    (Associativity::Flat, Eq) => Node::Compare { left, comparators: self.ast.list(&operands[1..]) },
+                    (Associativity::Flat, op) => Node::BoolOp { op, values: self.ast.list(&operands) },
+                    (_, op) => Node::BinOp { left, op, right: operands[1] },
+                };
+                left = self.ast.add(node, outer);
+            }
+        }
+
+        /// A name, constant, parenthesized expression, lambda or prefix operator
+        /// application allowed where operators tighter than `min` are parsed. If there is
+        /// none, tokens are skipped up to an operator looser than `min`, as `Parser`
+        /// skips up to the FOLLOW set of the level that failed.
+        fn operand(&mut self, min: u8) -> (NodeId, Span) {
+            let tok = self.lex.get_token();
+            let span = self.lex.get_span();
+            let prefix = match tok {
+                Token::Op(op) => PREFIX.iter().find(|p| p.op == op && p.power > min).copied(),
+                _ => None,
+            };
+            match tok {
+                Token::Lambda if min == LAMBDA => return self.lambda(),
+                Token::Variable(name) => {
+                    if let Some(w) = foreign_constant(&tok, span, &self.params) {
+                        self.diagnostics.push(w);
+                    }
+                    let name = self.ast.intern(name);
+                    self.bump();
+                    return (self.ast.add(Node::Name(name), span), span);
+                }
+                Token::Const(constant) => {
+                    let node = match constant {
+                        Constant::True => Node::True,
+                        Constant::False => Node::False,
+                        Constant::Number(text) => Node::Number(self.ast.intern(text)),
+                    };
+                    self.bump();
+                    return (self.ast.add(node, span), span);
+                }
+                Token::LParen => {
+                    if !self.enter() {
+                        return self.skip(|_| true);
+                    }
+                    self.bump();
+                    let (inner, _) = self.expression(LAMBDA);
+                    self.expect(Token::RParen, Some(span));
+                    self.depth -= 1;
+                    return (inner, Span { start: span.start, end: self.last_end });
+                }
+                _ => {}
+            }
+            if let Some(prefix) = prefix {
+                self.bump();
+                let (operand, outer) = self.expression(prefix.operand);
+                let span = Span { start: span.start, end: outer.end };
+                return (self.ast.add(Node::UnaryOp { op: prefix.op, operand }, span), span);
+            }
+
+            let e = unexpected(&tok, span, self.first(min));
+            self.error(e);
+            return self.skip(|tok| SYNC.contains(tok) || infix(tok).is_some_and(|i| i.power < min));
+        }
+
+        /// Tokens that may start an operand where operators tighter than `min` are parsed.
+        fn first(&self, min: u8) -> Vec<TokenKind> {
+            let mut first = Vec::new();
+            if min == LAMBDA {
+                first.push(TokenKind::Lambda);
+            }
+            first.push(TokenKind::Variable);
+            first.extend(PREFIX.iter().filter(|p| p.power > min).map(|p| TokenKind::Op(p.op)));
+            first.extend([TokenKind::Number, TokenKind::True, TokenKind::False, TokenKind::LParen]);
+            return first;
+        }
+
+    }
+}
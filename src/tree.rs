@@ -0,0 +1,170 @@
+pub mod tree {
+    //! Trees drawn with box-drawing characters, for a quick look in the terminal:
+    //! ```text
+    //! + 10..15
+    //! ├── x 10..11
+    //! └── 1 14..15
+    //! ```
+
+    use std::collections::HashMap;
+
+    use petgraph::graph::NodeIndex;
+
+    use crate::ast::ast::{Ast, Node, NodeId};
+    use crate::graphviz::graphviz::NodeKind;
+    use crate::lexer::lexer::Span;
+    use crate::parser::parser::{Parser, Tree};
+
+    const RESET: &str = "\x1b[0m";
+    const DIM: &str = "\x1b[2m";
+    const RED: &str = "\x1b[1;31m";
+    const GREEN: &str = "\x1b[32m";
+    const BLUE: &str = "\x1b[1;34m";
+
+    /// Deepest level drawn by default. Every line is indented by its depth, so a long
+    /// operator chain drawn in full would take space quadratic in its length.
+    pub const DEFAULT_TREE_DEPTH: usize = 100;
+
+    #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+    pub struct TreeOptions {
+        /// `|--` and `` `-- `` instead of `├──` and `└──`.
+        pub ascii: bool,
+        /// Prints the byte range of every node after its label.
+        pub spans: bool,
+        /// Colours nodes by kind with ANSI escapes, as `to_dot` does.
+        pub color: bool,
+        /// Children of the nodes this deep are drawn as one `…`; the root is at depth 0.
+        pub max_depth: usize,
+    }
+
+    impl Default for TreeOptions {
+        fn default() -> TreeOptions {
+            return TreeOptions { ascii: false, spans: false, color: false, max_depth: DEFAULT_TREE_DEPTH };
+        }
+    }
+
+    impl TreeOptions {
+        /// Branch to a child, the last child, and the indentation below each.
+        fn branches(&self) -> [&'static str; 4] {
+            if self.ascii {
+                return ["|-- ", "`-- ", "|   ", "    "];
+            }
+            return ["├── ", "└── ", "│   ", "    "];
+        }
+
+        /// What stands for the children left out below `max_depth`.
+        fn ellipsis(&self) -> &'static str {
+            if self.ascii {
+                return "...";
+            }
+            return "…";
+        }
+
+        fn paint(&self, style: &str, text: &str) -> String {
+            if !self.color {
+                return text.to_string();
+            }
+            return format!("{}{}{}", style, text, RESET);
+        }
+    }
+
+    /// Draws the tree below `root`, one node per line, down to `options.max_depth`.
+    fn draw<N: Copy>(
+        root: N,
+        node: impl Fn(N) -> (String, NodeKind, Option<Span>),
+        children: impl Fn(N) -> Vec<N>,
+        options: &TreeOptions,
+    ) -> String {
+        let [branch, last_branch, indent, last_indent] = options.branches();
+        let mut out = String::new();
+        // The node (`None` for an ellipsis), the indentation of its line, whether it is
+        // the last child and its depth.
+        let mut stack = vec![(Some(root), String::new(), None, 0)];
+        while let Some((id, prefix, last, depth)) = stack.pop() {
+            out.push_str(&prefix);
+            let prefix = match last {
+                None => prefix,
+                Some(true) => {
+                    out.push_str(last_branch);
+                    prefix + last_indent
+                }
+                Some(false) => {
+                    out.push_str(branch);
+                    prefix + indent
+                }
+            };
+            let Some(id) = id else {
+                out.push_str(&options.paint(DIM, options.ellipsis()));
+                out.push('\n');
+                continue;
+            };
+            let (label, kind, span) = node(id);
+            let style = match kind {
+                NodeKind::NonTerminal => BLUE,
+                NodeKind::Terminal => GREEN,
+                NodeKind::Epsilon => DIM,
+                NodeKind::Error => RED,
+            };
+            out.push_str(&options.paint(style, &label));
+            if let (true, Some(span)) = (options.spans, span) {
+                out.push(' ');
+                out.push_str(&options.paint(DIM, &format!("{}..{}", span.start, span.end)));
+            }
+            out.push('\n');
+
+            let children = children(id);
+            if depth == options.max_depth && !children.is_empty() {
+                stack.push((None, prefix, Some(true), depth + 1));
+                continue;
+            }
+            let count = children.len();
+            for (i, child) in children.into_iter().enumerate().rev() {
+                stack.push((Some(child), prefix.clone(), Some(i + 1 == count), depth + 1));
+            }
+        }
+        return out;
+    }
+
+    /// The derivation tree below `root`. A non-terminal spans its terminals, ε nothing.
+    pub fn render_derivation(parser: &Parser, root: NodeIndex, options: &TreeOptions) -> String {
+        let graph = &parser.graph;
+        let mut spans: HashMap<NodeIndex, Span> = HashMap::new();
+        if options.spans {
+            spans = parser.spans.clone();
+            let mut order = Vec::new();
+            let mut stack = vec![root];
+            while let Some(node) = stack.pop() {
+                order.push(node);
+                stack.extend(Tree::children(graph, node));
+            }
+            // Children come after their parent in `order`.
+            for node in order.into_iter().rev() {
+                let covered = Tree::children(graph, node).iter().filter_map(|child| spans.get(child).copied()).reduce(|a, b| Span {
+                    start: a.start.min(b.start),
+                    end: a.end.max(b.end),
+                });
+                if let (false, Some(span)) = (spans.contains_key(&node), covered) {
+                    spans.insert(node, span);
+                }
+            }
+        }
+        let node = |id: NodeIndex| (graph[id].clone(), NodeKind::of(parser, id), spans.get(&id).copied());
+        return draw(root, node, |id| Tree::children(graph, id), options);
+    }
+
+    /// The AST, from its root; an empty tree is drawn as `<empty>`.
+    pub fn render_ast(ast: &Ast, options: &TreeOptions) -> String {
+        let Some(root) = ast.root() else {
+            return "<empty>\n".to_string();
+        };
+        let node = |id: NodeId| {
+            let kind = match ast.node(id) {
+                Node::Error => NodeKind::Error,
+                Node::Name(_) | Node::Number(_) | Node::True | Node::False => NodeKind::Terminal,
+                _ => NodeKind::NonTerminal,
+            };
+            (ast.label(id), kind, Some(ast.span(id)))
+        };
+        return draw(root, node, |id| ast.children(id), options);
+    }
+}
@@ -0,0 +1,95 @@
+pub mod unparser {
+    use petgraph::Graph;
+    use petgraph::graph::NodeIndex;
+
+    use crate::parser::parser::Tree;
+
+    /// Delimiter priorities, same scale as Black uses: the higher the priority,
+    /// the earlier a too long line gets split on that operator.
+    pub const LOGIC_PRIORITY: u8 = 14;
+    pub const COMPARATOR_PRIORITY: u8 = 10;
+    pub const ARITH_PRIORITY: u8 = 5;
+    pub const TERM_PRIORITY: u8 = 4;
+
+    #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+    pub enum Role {
+        Keyword,
+        Atom,
+        Binary(u8),
+        Unary,
+        Open,
+        Close,
+        Comma,
+        Colon,
+    }
+
+    #[derive(Debug, Eq, PartialEq, Clone)]
+    pub struct Leaf {
+        pub text: String,
+        pub role: Role,
+    }
+
+    /// Terminals of the derivation tree rooted at `root`, left to right, with ε leaves dropped.
+    pub fn leaves(graph: &Graph<String, &str>, root: NodeIndex) -> Vec<Leaf> {
+        let mut result = Vec::new();
+        let mut stack = vec![(root, "")];
+        while let Some((node, parent)) = stack.pop() {
+            let children = Tree::children(graph, node);
+            let label = graph[node].as_str();
+            if children.is_empty() {
+                if let Some(role) = role(parent, label) {
+                    result.push(Leaf { text: label.to_string(), role });
+                }
+            }
+            stack.extend(children.into_iter().rev().map(|child| (child, label)));
+        }
+        return result;
+    }
+
+    /// Role of a terminal, given the label of the non-terminal it was derived from.
+    fn role(parent: &str, label: &str) -> Option<Role> {
+        let role = match (parent, label) {
+            (_, "ε") => return None,
+            ("S", "lambda") => Role::Keyword,
+            ("S", ":") => Role::Colon,
+            ("V'", ",") => Role::Comma,
+            ("Ep", _) | ("Op", _) => Role::Binary(LOGIC_PRIORITY),
+            ("Np", _) => Role::Binary(COMPARATOR_PRIORITY),
+            ("Qp", _) => Role::Binary(ARITH_PRIORITY),
+            ("Tp", _) => Role::Binary(TERM_PRIORITY),
+            ("A", _) | ("F", _) => Role::Unary,
+            ("C", "(") => Role::Open,
+            ("C", ")") => Role::Close,
+            _ => Role::Atom,
+        };
+        return Some(role);
+    }
+
+    /// Whitespace to put between two adjacent leaves.
+    pub fn whitespace(prev: &Leaf, cur: &Leaf) -> &'static str {
+        return match (prev.role, cur.role) {
+            (_, Role::Close) | (_, Role::Comma) | (_, Role::Colon) => "",
+            (Role::Open, _) => "",
+            (Role::Unary, _) if prev.text != "not" => "",
+            _ => " ",
+        };
+    }
+
+    /// Source text of the derivation tree rooted at `root`, on a single line. Parsing it
+    /// again gives back the same tree, unless the tree has error nodes.
+    pub fn unparse(graph: &Graph<String, &str>, root: NodeIndex) -> String {
+        return render(&leaves(graph, root));
+    }
+
+    /// Joins leaves on a single line using the canonical spacing.
+    pub fn render(leaves: &[Leaf]) -> String {
+        let mut result = String::new();
+        for (i, leaf) in leaves.iter().enumerate() {
+            if i > 0 {
+                result.push_str(whitespace(&leaves[i - 1], leaf));
+            }
+            result.push_str(&leaf.text);
+        }
+        return result;
+    }
+}
@@ -0,0 +1,234 @@
+pub mod visit {
+    //! Traversals of the `Ast`, driven by an explicit stack. `Visitor` and `VisitorMut`
+    //! get every node before its children and return whether to go on with them; `Fold`
+    //! gets every node after its children, which are already folded. An implementation
+    //! overrides the methods of the nodes it cares about.
+
+    use crate::ast::ast::{Ast, Node, NodeId, Symbol};
+    use crate::lexer::lexer::Operations;
+
+    enum Step {
+        Enter(NodeId),
+        Leave(NodeId),
+    }
+
+    /// Read-only traversal, e.g. for lint rules. A `visit_*` method returning `false`
+    /// skips the children of the node.
+    pub trait Visitor {
+        fn visit_lambda(&mut self, _ast: &Ast, _id: NodeId, _params: &[Symbol], _body: NodeId) -> bool {
+            return true;
+        }
+
+        fn visit_bool_op(&mut self, _ast: &Ast, _id: NodeId, _op: Operations, _values: &[NodeId]) -> bool {
+            return true;
+        }
+
+        fn visit_compare(&mut self, _ast: &Ast, _id: NodeId, _left: NodeId, _comparators: &[NodeId]) -> bool {
+            return true;
+        }
+
+        fn visit_bin_op(&mut self, _ast: &Ast, _id: NodeId, _left: NodeId, _op: Operations, _right: NodeId) -> bool {
+            return true;
+        }
+
+        fn visit_unary_op(&mut self, _ast: &Ast, _id: NodeId, _op: Operations, _operand: NodeId) -> bool {
+            return true;
+        }
+
+        fn visit_name(&mut self, _ast: &Ast, _id: NodeId, _name: Symbol) {}
+
+        fn visit_number(&mut self, _ast: &Ast, _id: NodeId, _value: Symbol) {}
+
+        /// `True` or `False`.
+        fn visit_constant(&mut self, _ast: &Ast, _id: NodeId, _value: bool) {}
+
+        fn visit_error(&mut self, _ast: &Ast, _id: NodeId) {}
+
+        /// Called after the children of `id`, e.g. to leave a scope.
+        fn leave_node(&mut self, _ast: &Ast, _id: NodeId) {}
+    }
+
+    /// Calls the `visit_*` method of the kind of `id`; true if its children are next.
+    fn visit_one<V: Visitor + ?Sized>(v: &mut V, ast: &Ast, id: NodeId) -> bool {
+        match ast.node(id) {
+            Node::Lambda { params, body } => return v.visit_lambda(ast, id, ast.params(params), body),
+            Node::BoolOp { op, values } => return v.visit_bool_op(ast, id, op, ast.items(values)),
+            Node::Compare { left, comparators } => return v.visit_compare(ast, id, left, ast.items(comparators)),
+            Node::BinOp { left, op, right } => return v.visit_bin_op(ast, id, left, op, right),
+            Node::UnaryOp { op, operand } => return v.visit_unary_op(ast, id, op, operand),
+            Node::Name(name) => v.visit_name(ast, id, name),
+            Node::Number(value) => v.visit_number(ast, id, value),
+            Node::True => v.visit_constant(ast, id, true),
+            Node::False => v.visit_constant(ast, id, false),
+            Node::Error => v.visit_error(ast, id),
+        }
+        return false;
+    }
+
+    /// Visits the subtree at `id`, every node before its children, left to right.
+    pub fn walk_node<V: Visitor + ?Sized>(v: &mut V, ast: &Ast, id: NodeId) {
+        let mut stack = vec![Step::Enter(id)];
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Leave(id) => v.leave_node(ast, id),
+                Step::Enter(id) => {
+                    stack.push(Step::Leave(id));
+                    if visit_one(v, ast, id) {
+                        stack.extend(ast.children(id).into_iter().rev().map(Step::Enter));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Visits the whole tree.
+    pub fn visit<V: Visitor + ?Sized>(v: &mut V, ast: &Ast) {
+        if let Some(root) = ast.root() {
+            walk_node(v, ast, root);
+        }
+    }
+
+    /// Traversal that may change nodes in place with `Ast::replace`, e.g. renaming.
+    /// A method gets the node as it is when visited; the children visited after it
+    /// are those of the node it leaves at `id`, unless it returns `false`.
+    pub trait VisitorMut {
+        fn visit_lambda_mut(&mut self, _ast: &mut Ast, _id: NodeId) -> bool {
+            return true;
+        }
+
+        fn visit_bool_op_mut(&mut self, _ast: &mut Ast, _id: NodeId) -> bool {
+            return true;
+        }
+
+        fn visit_compare_mut(&mut self, _ast: &mut Ast, _id: NodeId) -> bool {
+            return true;
+        }
+
+        fn visit_bin_op_mut(&mut self, _ast: &mut Ast, _id: NodeId) -> bool {
+            return true;
+        }
+
+        fn visit_unary_op_mut(&mut self, _ast: &mut Ast, _id: NodeId) -> bool {
+            return true;
+        }
+
+        /// Names, numbers, `True`, `False` and error nodes.
+        fn visit_leaf_mut(&mut self, _ast: &mut Ast, _id: NodeId) {}
+
+        /// Called after the children of `id`.
+        fn leave_node_mut(&mut self, _ast: &mut Ast, _id: NodeId) {}
+    }
+
+    /// Calls the `visit_*_mut` method of the kind of `id`; true if its children are next.
+    fn visit_one_mut<V: VisitorMut + ?Sized>(v: &mut V, ast: &mut Ast, id: NodeId) -> bool {
+        match ast.node(id) {
+            Node::Lambda { .. } => return v.visit_lambda_mut(ast, id),
+            Node::BoolOp { .. } => return v.visit_bool_op_mut(ast, id),
+            Node::Compare { .. } => return v.visit_compare_mut(ast, id),
+            Node::BinOp { .. } => return v.visit_bin_op_mut(ast, id),
+            Node::UnaryOp { .. } => return v.visit_unary_op_mut(ast, id),
+            Node::Name(_) | Node::Number(_) | Node::True | Node::False | Node::Error => v.visit_leaf_mut(ast, id),
+        }
+        return false;
+    }
+
+    /// Visits the subtree at `id`, every node before its current children.
+    pub fn walk_node_mut<V: VisitorMut + ?Sized>(v: &mut V, ast: &mut Ast, id: NodeId) {
+        let mut stack = vec![Step::Enter(id)];
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Leave(id) => v.leave_node_mut(ast, id),
+                Step::Enter(id) => {
+                    stack.push(Step::Leave(id));
+                    if visit_one_mut(v, ast, id) {
+                        stack.extend(ast.children(id).into_iter().rev().map(Step::Enter));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Visits the whole tree.
+    pub fn visit_mut<V: VisitorMut + ?Sized>(v: &mut V, ast: &mut Ast) {
+        if let Some(root) = ast.root() {
+            walk_node_mut(v, ast, root);
+        }
+    }
+
+    /// Rewriting into a new tree, e.g. constant folding. The children of a node are
+    /// folded first: its method gets their ids in `out`, adds the node it turns `id`
+    /// into to `out` and returns it. By default the node is copied.
+    pub trait Fold {
+        fn fold_lambda(&mut self, ast: &Ast, id: NodeId, params: &[Symbol], body: NodeId, out: &mut Ast) -> NodeId {
+            let params: Vec<Symbol> = params.iter().map(|p| out.intern(ast.name(*p))).collect();
+            let params = out.param_list(&params);
+            return out.add(Node::Lambda { params, body }, ast.span(id));
+        }
+
+        fn fold_bool_op(&mut self, ast: &Ast, id: NodeId, op: Operations, values: &[NodeId], out: &mut Ast) -> NodeId {
+            let values = out.list(values);
+            return out.add(Node::BoolOp { op, values }, ast.span(id));
+        }
+
+        fn fold_compare(&mut self, ast: &Ast, id: NodeId, left: NodeId, comparators: &[NodeId], out: &mut Ast) -> NodeId {
+            let comparators = out.list(comparators);
+            return out.add(Node::Compare { left, comparators }, ast.span(id));
+        }
+
+        fn fold_bin_op(&mut self, ast: &Ast, id: NodeId, left: NodeId, op: Operations, right: NodeId, out: &mut Ast) -> NodeId {
+            return out.add(Node::BinOp { left, op, right }, ast.span(id));
+        }
+
+        fn fold_unary_op(&mut self, ast: &Ast, id: NodeId, op: Operations, operand: NodeId, out: &mut Ast) -> NodeId {
+            return out.add(Node::UnaryOp { op, operand }, ast.span(id));
+        }
+
+        /// Names, numbers, `True`, `False` and error nodes.
+        fn fold_leaf(&mut self, ast: &Ast, id: NodeId, out: &mut Ast) -> NodeId {
+            let node = match ast.node(id) {
+                Node::Name(name) => Node::Name(out.intern(ast.name(name))),
+                Node::Number(value) => Node::Number(out.intern(ast.name(value))),
+                node => node,
+            };
+            return out.add(node, ast.span(id));
+        }
+    }
+
+    /// Folds the subtree at `id` into `out` and returns the node it became.
+    pub fn fold_node<F: Fold + ?Sized>(f: &mut F, ast: &Ast, id: NodeId, out: &mut Ast) -> NodeId {
+        // Folded children wait on `done` until their parent is left.
+        let mut done: Vec<NodeId> = Vec::new();
+        let mut stack = vec![Step::Enter(id)];
+        while let Some(step) = stack.pop() {
+            let id = match step {
+                Step::Enter(id) => {
+                    stack.push(Step::Leave(id));
+                    stack.extend(ast.children(id).into_iter().rev().map(Step::Enter));
+                    continue;
+                }
+                Step::Leave(id) => id,
+            };
+            let children = done.split_off(done.len() - ast.children(id).len());
+            let folded = match ast.node(id) {
+                Node::Lambda { params, .. } => f.fold_lambda(ast, id, ast.params(params), children[0], out),
+                Node::BoolOp { op, .. } => f.fold_bool_op(ast, id, op, &children, out),
+                Node::Compare { .. } => f.fold_compare(ast, id, children[0], &children[1..], out),
+                Node::BinOp { op, .. } => f.fold_bin_op(ast, id, children[0], op, children[1], out),
+                Node::UnaryOp { op, .. } => f.fold_unary_op(ast, id, op, children[0], out),
+                Node::Name(_) | Node::Number(_) | Node::True | Node::False | Node::Error => f.fold_leaf(ast, id, out),
+            };
+            done.push(folded);
+        }
+        return done[0];
+    }
+
+    /// Folds the whole tree into a new one.
+    pub fn fold<F: Fold + ?Sized>(f: &mut F, ast: &Ast) -> Ast {
+        let mut out = Ast::new();
+        if let Some(root) = ast.root() {
+            let root = fold_node(f, ast, root, &mut out);
+            out.set_root(root);
+        }
+        return out;
+    }
+}
@@ -0,0 +1,30 @@
+#![allow(clippy::needless_return)]
+
+//! Arguments of `lambda-parser` itself, the subcommands aside.
+
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    return Command::new(env!("CARGO_BIN_EXE_lambda-parser")).args(args).output().unwrap();
+}
+
+#[test]
+fn unknown_options_are_usage_errors() {
+    let out = run(&["--rankdr", "LR", "lambda x: x"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(out.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&out.stderr).starts_with("error: unexpected argument --rankdr\nusage:"));
+}
+
+#[test]
+fn options_and_source() {
+    let out = run(&["--rankdir", "LR", "--hide-epsilon", "lambda x: x"]);
+    assert_eq!(out.status.code(), Some(0));
+    let dot = String::from_utf8_lossy(&out.stdout);
+    assert!(dot.contains("rankdir=LR"), "{}", dot);
+    assert!(!dot.contains("ε"), "{}", dot);
+
+    let out = run(&["--emit", "ast", "lambda x: x +"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "(lambda (x) (+ x <error>))\n");
+}
@@ -0,0 +1,63 @@
+#![allow(clippy::needless_return)]
+
+//! `explain` and `lambda-parser --explain`.
+
+use std::process::{Command, Output};
+
+use lambda_parser::codes::codes::{self, explain};
+
+const ALL: &[&str] = &[
+    codes::UNEXPECTED_CHARACTER,
+    codes::UNCLOSED_PARENTHESIS,
+    codes::MISSING_COLON,
+    codes::UNMATCHED_PARENTHESIS,
+    codes::UNEXPECTED_TOKEN,
+    codes::MISSING_LAMBDA,
+    codes::INVALID_PARAMETERS,
+    codes::FOREIGN_OPERATOR,
+    codes::NOT_EQUAL,
+    codes::ASSIGNMENT,
+    codes::TRUE_DIVISION,
+    codes::FOREIGN_CONSTANT,
+    codes::TOO_DEEP,
+    codes::LIMIT_EXCEEDED,
+    codes::INVALID_NUMBER,
+];
+
+#[test]
+fn every_code_is_explained() {
+    for (i, code) in ALL.iter().enumerate() {
+        assert_eq!(*code, format!("LP{:04}", i + 1), "codes are numbered in order");
+        let text = explain(code).unwrap_or_else(|| panic!("{} has no explanation", code));
+        assert!(!text.trim().is_empty(), "{} has an empty explanation", code);
+    }
+    assert_eq!(explain(&format!("LP{:04}", ALL.len() + 1)), None, "a code is missing from ALL");
+}
+
+#[test]
+fn lookup_ignores_case() {
+    assert_eq!(explain("lp0010"), explain(codes::ASSIGNMENT));
+    assert_eq!(explain("Lp0015"), explain(codes::INVALID_NUMBER));
+    assert!(explain("lp0010").is_some());
+    assert_eq!(explain("LP9999"), None);
+    assert_eq!(explain(""), None);
+}
+
+fn cli(args: &[&str]) -> Output {
+    return Command::new(env!("CARGO_BIN_EXE_lambda-parser")).args(args).output().unwrap();
+}
+
+#[test]
+fn cli_exit_codes() {
+    let out = cli(&["--explain", "lp0010"]);
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&out.stdout), explain(codes::ASSIGNMENT).unwrap());
+
+    let out = cli(&["--explain", "LP9999"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(out.stdout.is_empty());
+    assert_eq!(String::from_utf8_lossy(&out.stderr), "error: LP9999 is not a valid error code\n");
+
+    let out = cli(&["--explain"]);
+    assert_ne!(out.status.code(), Some(0));
+}
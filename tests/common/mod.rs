@@ -0,0 +1,22 @@
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Inputs of the corpus in tests/golden, sorted by name.
+pub fn corpus() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let mut inputs: Vec<PathBuf> = fs::read_dir(dir)
+        .expect("tests/golden is readable")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "py"))
+        .collect();
+    inputs.sort();
+    return inputs;
+}
+
+/// The lambda in `path`, without the newline that ends the file.
+pub fn source(path: &Path) -> String {
+    let src = fs::read_to_string(path).unwrap();
+    return src.strip_suffix('\n').unwrap_or(&src).to_string();
+}
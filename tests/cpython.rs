@@ -0,0 +1,145 @@
+#![allow(clippy::needless_return)]
+
+//! Differential test against CPython: every input is read by `ast.parse(..., mode="eval")`
+//! too. Where both accept it, the trees must be the same, both written as the
+//! S-expressions of `Ast`'s `Display`; otherwise both must reject it. Skipped when there
+//! is no `python3`, or `$PYTHON`, to run.
+
+use std::env;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use lambda_parser::generator::generator::{Generator, GeneratorOptions};
+use lambda_parser::grammar::grammar::NonTerminal;
+use lambda_parser::lower::lower::lower;
+use lambda_parser::parser::parser::Parser;
+
+mod common;
+use common::{corpus, source};
+
+/// How many generated lambdas of each identifier pool are compared on top of the corpus.
+const GENERATED: usize = 2000;
+
+/// Reads a JSON list of sources on stdin and prints one S-expression, or `error: ...`,
+/// per line. Python that is no lambda of our grammar, such as `x / 2` or `lambda: None`,
+/// counts as an error.
+const SCRIPT: &str = r#"
+import ast, json, sys
+
+class Unsupported(Exception):
+    pass
+
+OPS = {
+    ast.Or: "or", ast.And: "and", ast.Not: "not", ast.Eq: "==", ast.Add: "+", ast.Sub: "-",
+    ast.Mult: "*", ast.FloorDiv: "//", ast.Mod: "%", ast.UAdd: "+", ast.USub: "-",
+}
+
+def op(node):
+    if type(node) not in OPS:
+        raise Unsupported("`%s` is not in the grammar" % type(node).__name__)
+    return OPS[type(node)]
+
+def sexpr(node):
+    if isinstance(node, ast.Lambda):
+        args = node.args
+        if args.posonlyargs or args.vararg or args.kwonlyargs or args.kwarg or args.defaults:
+            raise Unsupported("only plain parameters are in the grammar")
+        params = " ".join(arg.arg for arg in args.args)
+        return "(lambda (%s) %s)" % (params, sexpr(node.body))
+    if isinstance(node, ast.BoolOp):
+        return "(%s %s)" % (op(node.op), " ".join(map(sexpr, node.values)))
+    if isinstance(node, ast.Compare):
+        ops = {op(o) for o in node.ops}
+        return "(%s %s)" % (" ".join(sorted(ops)), " ".join(map(sexpr, [node.left] + node.comparators)))
+    if isinstance(node, ast.BinOp):
+        return "(%s %s %s)" % (op(node.op), sexpr(node.left), sexpr(node.right))
+    if isinstance(node, ast.UnaryOp):
+        return "(%s %s)" % (op(node.op), sexpr(node.operand))
+    if isinstance(node, ast.Name):
+        return node.id
+    if isinstance(node, ast.Constant) and type(node.value) in (int, bool):
+        return repr(node.value)
+    raise Unsupported("`%s` is not in the grammar" % ast.unparse(node))
+
+for src in json.load(sys.stdin):
+    try:
+        # Our lexer takes newlines for blanks, which Python does only inside brackets.
+        tree = ast.parse("(" + src + ")", mode="eval").body
+        if not isinstance(tree, ast.Lambda):
+            raise Unsupported("not a lambda")
+        print(sexpr(tree))
+    except (SyntaxError, ValueError, RecursionError, Unsupported) as e:
+        print("error: %s" % e)
+"#;
+
+/// The interpreter to compare with, if one runs.
+fn python() -> Option<String> {
+    let python = env::var("PYTHON").unwrap_or_else(|_| "python3".to_string());
+    let output = Command::new(&python).arg("--version").output().ok()?;
+    return if output.status.success() { Some(python) } else { None };
+}
+
+/// CPython's S-expression of every source, in order.
+fn cpython(python: &str, sources: &[String]) -> Vec<String> {
+    let mut child = Command::new(python)
+        .args(["-c", SCRIPT])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("python runs");
+    let input = serde_json::to_string(sources).unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{} failed", python);
+    return String::from_utf8(output.stdout).unwrap().lines().map(String::from).collect();
+}
+
+#[test]
+fn matches_cpython() {
+    let Some(python) = python() else {
+        eprintln!("skipped: no python3 to compare with, set PYTHON to use another interpreter");
+        return;
+    };
+
+    let mut sources: Vec<String> = corpus().iter().map(|path| source(path)).collect();
+    let mut generator = Generator::new(GeneratorOptions::default());
+    sources.extend((0..GENERATED).map(|_| generator.lambda()));
+    // Variables named like the non-terminals they sit under in the derivation tree.
+    let identifiers = NonTerminal::ALL
+        .iter()
+        .map(|nt| nt.label())
+        .filter(|label| label.bytes().all(|c| c.is_ascii_alphanumeric()))
+        .map(String::from)
+        .collect();
+    let mut generator = Generator::new(GeneratorOptions { identifiers, ..GeneratorOptions::default() });
+    sources.extend((0..GENERATED).map(|_| generator.lambda()));
+
+    // The tree, or the first error.
+    let ours: Vec<Result<String, String>> = sources
+        .iter()
+        .map(|src| {
+            let mut parser = Parser::get(src);
+            let root = parser.parse();
+            return match parser.diagnostics.iter().find(|d| d.is_error()) {
+                Some(e) => Err(format!("error[{}]: {}", e.code.unwrap_or("-"), e.message)),
+                None => Ok(lower(&parser, root).ast.to_string()),
+            };
+        })
+        .collect();
+
+    let theirs = cpython(&python, &sources);
+    assert_eq!(theirs.len(), sources.len(), "{} printed one line per source", python);
+    let mismatches: Vec<String> = sources
+        .iter()
+        .zip(ours.iter().zip(&theirs))
+        .filter(|(_, (ours, theirs))| match ours {
+            Ok(ours) => ours != *theirs,
+            Err(_) => !theirs.starts_with("error:"),
+        })
+        .map(|(src, (ours, theirs))| {
+            let ours = ours.as_ref().unwrap_or_else(|e| e);
+            return format!("{:?}\n  ours:    {}\n  CPython: {}", src, ours, theirs);
+        })
+        .collect();
+    assert!(mismatches.is_empty(), "{} of {} inputs differ:\n{}", mismatches.len(), sources.len(), mismatches.join("\n"));
+}
@@ -0,0 +1,130 @@
+#![allow(clippy::needless_return)]
+
+//! `format_lambda` and `format_source`, and the exit codes of `lambda-parser fmt`.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use lambda_parser::codes::codes;
+use lambda_parser::formatter::formatter::{format_lambda, format_source, FormatError, DEFAULT_LINE_LENGTH};
+
+mod common;
+use common::{corpus, source};
+
+#[test]
+fn spacing() {
+    let cases = [
+        ("lambda x,y:x+-y", "lambda x, y: x + -y"),
+        ("lambda:not(a)and b%2==0", "lambda: not (a) and b % 2 == 0"),
+        ("lambda  x :  x//2 *  + 3", "lambda x: x // 2 * +3"),
+        ("lambda x: (\n  x\n)", "lambda x: (x)"),
+    ];
+    for (src, expected) in cases {
+        assert_eq!(format_lambda(src, DEFAULT_LINE_LENGTH), Ok(expected.to_string()), "{:?}", src);
+    }
+}
+
+#[test]
+fn wrapping() {
+    let src = "lambda alpha, beta: alpha * (beta + 100000) - (alpha // 3 == beta or not alpha)";
+    assert_eq!(format_lambda(src, 88).unwrap(), src);
+    let wrapped = format_lambda(src, 30).unwrap();
+    assert_eq!(
+        wrapped,
+        "lambda alpha, beta: alpha * (\n    beta + 100000\n) - (\n    alpha // 3 == beta\n    or not alpha\n)"
+    );
+    assert!(wrapped.lines().all(|line| line.chars().count() <= 30), "{}", wrapped);
+}
+
+#[test]
+fn idempotence() {
+    for path in corpus() {
+        let src = source(&path);
+        for line_length in [20, 40, DEFAULT_LINE_LENGTH] {
+            let Ok(once) = format_lambda(&src, line_length) else {
+                continue;
+            };
+            assert_eq!(format_lambda(&once, line_length).as_ref(), Ok(&once), "{}", path.display());
+        }
+    }
+}
+
+#[test]
+fn files() {
+    let src = "lambda x:x\n\n\n\nlambda y:(y+\n1)\n";
+    assert_eq!(format_source(src, DEFAULT_LINE_LENGTH).unwrap(), "lambda x: x\n\n\nlambda y: (y + 1)\n");
+}
+
+#[test]
+fn errors() {
+    let Err(FormatError::Syntax(e)) = format_lambda("lambda x: x +", DEFAULT_LINE_LENGTH) else {
+        panic!("a dangling operator is formatted");
+    };
+    assert!(e.is_error());
+    // Nothing after a NUL byte may be dropped.
+    let Err(FormatError::Syntax(e)) = format_lambda("lambda x: x\0 + 1", DEFAULT_LINE_LENGTH) else {
+        panic!("a NUL byte is formatted");
+    };
+    assert_eq!(e.code, Some(codes::UNEXPECTED_CHARACTER));
+}
+
+fn fmt(args: &[&str]) -> Output {
+    return Command::new(env!("CARGO_BIN_EXE_lambda-parser")).arg("fmt").args(args).output().unwrap();
+}
+
+/// A file in the temporary directory, named after the test.
+fn file(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("lambda-parser-{}-{}.py", std::process::id(), name));
+    fs::write(&path, contents).unwrap();
+    return path;
+}
+
+#[test]
+fn check_exit_codes() {
+    let formatted = file("formatted", "lambda x, y: x + -y\n");
+    let unformatted = file("unformatted", "lambda x,y:x+-y\n");
+    let invalid = file("invalid", "lambda x: x +\n");
+    let nul = file("nul", "lambda x: x\0 + 1\n");
+
+    let out = fmt(&["--check", formatted.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(0));
+    let out = fmt(&["--check", unformatted.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&out.stderr).contains(&format!("would reformat {}", unformatted.display())));
+    let out = fmt(&["--check", invalid.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(123));
+    let out = fmt(&["--check", nul.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(123));
+    // The worst status wins.
+    let out = fmt(&["--check", formatted.to_str().unwrap(), unformatted.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(1));
+    // `--check` never writes.
+    assert_eq!(fs::read_to_string(&unformatted).unwrap(), "lambda x,y:x+-y\n");
+
+    let out = fmt(&["--check", "-c", "lambda x, y: x + -y"]);
+    assert_eq!(out.status.code(), Some(0));
+    let out = fmt(&["--check", "-c", "lambda x,y:x+-y"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&out.stderr), "would reformat -c\n");
+
+    // Fits in 88 columns, not in 30.
+    let long = "lambda alpha, beta: alpha * (beta + 100000) - (alpha // 3 == beta or not alpha)";
+    let out = fmt(&["--check", "-c", long]);
+    assert_eq!(out.status.code(), Some(0));
+    let out = fmt(&["--check", "--line-length", "30", "-c", long]);
+    assert_eq!(out.status.code(), Some(1));
+
+    for path in [formatted, unformatted, invalid, nul] {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn rewrites_files() {
+    let path = file("rewrite", "lambda x,y:x+-y\n");
+    let out = fmt(&[path.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "lambda x, y: x + -y\n");
+    fs::remove_file(path).unwrap();
+}
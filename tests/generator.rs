@@ -0,0 +1,85 @@
+#![allow(clippy::needless_return)]
+
+//! `Generator`: valid lambdas, mutants that never parse cleanly, reproducibility and
+//! the identifiers it accepts.
+
+use std::process::Command;
+
+use lambda_parser::generator::generator::{is_identifier, Generator, GeneratorOptions};
+use lambda_parser::parser::parser::Parser;
+use lambda_parser::pratt::pratt::PrattParser;
+
+const COUNT: usize = 1000;
+
+fn options(seed: u64, max_depth: usize) -> GeneratorOptions {
+    return GeneratorOptions { seed, max_depth, ..GeneratorOptions::default() };
+}
+
+#[test]
+fn lambdas_parse_without_diagnostics() {
+    for max_depth in [1, 10, 40] {
+        let mut generator = Generator::new(options(7, max_depth));
+        for _ in 0..COUNT {
+            let src = generator.lambda();
+            let mut parser = Parser::get(&src);
+            parser.parse();
+            assert!(parser.diagnostics.is_empty(), "{:?}: {:?}", src, parser.diagnostics);
+            let mut pratt = PrattParser::get(&src);
+            pratt.parse();
+            assert!(pratt.diagnostics.is_empty(), "{:?}: {:?}", src, pratt.diagnostics);
+        }
+    }
+}
+
+#[test]
+fn mutants_have_errors() {
+    // Shallow lambdas are few, so many edits give back another valid one.
+    for max_depth in [1, 10, 40] {
+        let mut generator = Generator::new(options(7, max_depth));
+        for _ in 0..COUNT {
+            let src = generator.mutant();
+            let mut parser = Parser::get(&src);
+            parser.parse();
+            assert!(parser.diagnostics.iter().any(|d| d.is_error()), "{:?} parses", src);
+        }
+    }
+}
+
+#[test]
+fn same_seed_same_output() {
+    let run = |seed| {
+        let mut generator = Generator::new(options(seed, 20));
+        return (0..100).map(|i| if i % 2 == 0 { generator.lambda() } else { generator.mutant() }).collect::<Vec<_>>();
+    };
+    assert_eq!(run(42), run(42));
+    assert_ne!(run(42), run(43));
+}
+
+#[test]
+fn identifiers() {
+    for name in ["x", "_", "bar_1", "V", "Ep", "xé"] {
+        assert!(is_identifier(name), "{}", name);
+    }
+    for name in ["lambda", "and", "or", "not", "True", "False", "", "1x", "a b", " x", "x,y", "é"] {
+        assert!(!is_identifier(name), "{:?}", name);
+    }
+}
+
+#[test]
+#[should_panic(expected = "`True` is not an identifier")]
+fn keywords_are_no_identifiers() {
+    let identifiers = vec!["x".to_string(), "True".to_string()];
+    Generator::new(GeneratorOptions { identifiers, ..GeneratorOptions::default() });
+}
+
+#[test]
+fn cli_rejects_keywords() {
+    let generate = |identifiers: &str| {
+        let args = ["generate", "--identifiers", identifiers];
+        return Command::new(env!("CARGO_BIN_EXE_lambda-parser")).args(args).output().unwrap();
+    };
+    let out = generate("x,lambda");
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).starts_with("error: --identifiers: `lambda` is a keyword"));
+    assert_eq!(generate("x,y").status.code(), Some(0));
+}
@@ -0,0 +1,140 @@
+#![allow(clippy::needless_return)]
+
+//! Snapshot tests: for every `tests/golden/NAME.py`, the tokens, the AST, the DOT output
+//! and the diagnostics are compared with `NAME.tokens`, `NAME.ast`, `NAME.dot` and
+//! `NAME.errors` next to it. `BLESS=1 cargo test --test golden` writes them instead.
+
+use std::collections::HashSet;
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use petgraph::graph::NodeIndex;
+
+use lambda_parser::diagnostic::diagnostic::Renderer;
+use lambda_parser::grammar::grammar::{NonTerminal, Symbol, PRODUCTIONS};
+use lambda_parser::graphviz::graphviz::{to_dot, DotOptions};
+use lambda_parser::lexer::lexer::{tokenize, Lexer};
+use lambda_parser::lower::lower::lower;
+use lambda_parser::parser::parser::{Parser, Tree, ERROR};
+use lambda_parser::pratt::pratt::PrattParser;
+
+mod common;
+use common::{corpus, source};
+
+/// One token per line with its span; lexing errors in between.
+fn tokens(src: &str) -> String {
+    let mut out = String::new();
+    for item in Lexer::get(src) {
+        match item {
+            Ok(token) => writeln!(out, "{}..{} {:?}", token.span.start, token.span.end, token.token),
+            Err(e) => writeln!(out, "error[{}] {}..{}: {}", e.0.code.unwrap_or("-"), e.0.span.start, e.0.span.end, e),
+        }
+        .unwrap();
+    }
+    return out;
+}
+
+/// Compares `actual` with the snapshot at `path`, or overwrites it when blessing.
+fn check(path: &Path, actual: &str, failures: &mut Vec<String>) {
+    let expected = fs::read_to_string(path).ok();
+    if expected.as_deref() == Some(actual) {
+        return;
+    }
+    if env::var_os("BLESS").is_some() {
+        fs::write(path, actual).unwrap();
+        return;
+    }
+    let name = path.file_name().unwrap().to_string_lossy();
+    match expected {
+        Some(expected) => failures.push(format!("{} differs\n--- expected\n{}--- actual\n{}", name, expected, actual)),
+        None => failures.push(format!("{} is missing, run with BLESS=1 to create it", name)),
+    }
+}
+
+#[test]
+fn golden() {
+    let mut failures = Vec::new();
+    for path in corpus() {
+        let src = source(&path);
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+
+        let mut parser = Parser::get(&src);
+        let root = parser.parse();
+        let ast = lower(&parser, root).ast;
+        let dot = to_dot(&parser, root, &DotOptions::default());
+        let renderer = Renderer { source: &src, name: &name, color: false };
+        let errors: String = parser.diagnostics.iter().map(|d| renderer.render(d) + "\n").collect();
+
+        // The other parsers are not snapshotted, they have to agree with this one.
+        let mut table = Parser::get(&src);
+        let table_root = table.parse_table();
+        assert_eq!(dot, to_dot(&table, table_root, &DotOptions::default()), "table-driven parser on {}", name);
+        assert_eq!(parser.diagnostics, table.diagnostics, "table-driven parser on {}", name);
+        let mut pratt = PrattParser::get(&src);
+        pratt.parse();
+        assert!(ast == pratt.ast, "Pratt parser on {}: {} != {}", name, ast, pratt.ast);
+        assert_eq!(parser.diagnostics, pratt.diagnostics, "Pratt parser on {}", name);
+
+        check(&path.with_extension("tokens"), &tokens(&src), &mut failures);
+        check(&path.with_extension("ast"), &format!("{}\n", ast), &mut failures);
+        check(&path.with_extension("dot"), &dot, &mut failures);
+        check(&path.with_extension("errors"), &errors, &mut failures);
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+/// Indices in `PRODUCTIONS` of the productions derived by `parser`, error nodes aside.
+/// Terminals have a span: a variable may be called like a non-terminal.
+fn productions(parser: &Parser, root: NodeIndex) -> HashSet<usize> {
+    let graph = &parser.graph;
+    let mut used = HashSet::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        let children = Tree::children(graph, node);
+        let labels: Vec<&str> = children.iter().map(|child| graph[*child].as_str()).collect();
+        let terminals: Vec<bool> = children.iter().map(|child| parser.spans.contains_key(child)).collect();
+        stack.extend(children);
+        if parser.spans.contains_key(&node) {
+            continue;
+        }
+        let Some(nt) = NonTerminal::ALL.into_iter().find(|nt| nt.label() == graph[node]) else {
+            continue;
+        };
+        if labels.contains(&ERROR) {
+            continue;
+        }
+        let derived = PRODUCTIONS.iter().position(|p| {
+            if p.lhs != nt {
+                return false;
+            }
+            if p.rhs.is_empty() {
+                return labels == ["ε"];
+            }
+            return p.rhs.len() == labels.len()
+                && p.rhs.iter().zip(labels.iter().zip(&terminals)).all(|(symbol, (label, terminal))| match symbol {
+                    Symbol::N(nt) => !terminal && nt.label() == *label,
+                    Symbol::T(kind) => tokenize(label).first().map(|t| t.token.kind()) == Some(*kind),
+                });
+        });
+        used.insert(derived.unwrap_or_else(|| panic!("{} → {:?} is no production", nt.name(), labels)));
+    }
+    return used;
+}
+
+#[test]
+fn every_production_is_covered() {
+    let mut used = HashSet::new();
+    for path in corpus() {
+        let src = source(&path);
+        let mut parser = Parser::get(&src);
+        let root = parser.parse();
+        used.extend(productions(&parser, root));
+    }
+    let missing: Vec<String> = (0..PRODUCTIONS.len())
+        .filter(|i| !used.contains(i))
+        .map(|i| PRODUCTIONS[i].to_string())
+        .collect();
+    assert!(missing.is_empty(), "no input of tests/golden derives {}", missing.join(", "));
+}
@@ -0,0 +1 @@
+lambda x: x + 1 - 2 * 3 // 4 % 5
@@ -0,0 +1 @@
+lambda: (Hello == 42 or not (Hello == 24)) -92 - 152 * 3 // 2 + 33 -varvarvar * 92 %world
@@ -0,0 +1 @@
+lambda x: true or null
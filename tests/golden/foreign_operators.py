@@ -0,0 +1 @@
+lambda a, b: a && b || !a
@@ -0,0 +1 @@
+lambda S': S' + ε
@@ -0,0 +1 @@
+lambda x: 1abc * 0x1F + x
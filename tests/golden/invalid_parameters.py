@@ -0,0 +1 @@
+lambda 1, x: x
@@ -0,0 +1 @@
+lambda: 01 + 00
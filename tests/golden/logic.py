@@ -0,0 +1 @@
+lambda a, b: a or b and not c or True
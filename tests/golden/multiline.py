@@ -0,0 +1,2 @@
+lambda x:
+    x + 1
@@ -0,0 +1 @@
+lambda x: lambda y: x
@@ -0,0 +1 @@
+lambda: 0
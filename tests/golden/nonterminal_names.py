@@ -0,0 +1 @@
+lambda S, V, E: not V or E == S + Ep * (Qp - Tp) // F % C
@@ -0,0 +1 @@
+lambda V, W: V
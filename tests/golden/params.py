@@ -0,0 +1 @@
+lambda x, y,: x
@@ -0,0 +1 @@
+lambda: (lambda x: (x))
@@ -0,0 +1 @@
+lambda x, V: V
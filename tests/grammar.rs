@@ -0,0 +1,49 @@
+#![allow(clippy::needless_return)]
+
+//! `conflicts` on the grammar of README.md and on small grammars that are not LL(1).
+
+use lambda_parser::grammar::grammar::{conflicts, sets, Conflict, ConflictKind, NonTerminal as NT, Production, Sets, Symbol, PRODUCTIONS};
+use lambda_parser::lexer::lexer::TokenKind;
+
+use Symbol::{N, T};
+
+/// `S → x V`, `S → x`, `V → ,`: both productions of `S` start with an identifier.
+const FIRST_FIRST: &[Production] = &[
+    Production { lhs: NT::S, rhs: &[T(TokenKind::Variable), N(NT::V)] },
+    Production { lhs: NT::S, rhs: &[T(TokenKind::Variable)] },
+    Production { lhs: NT::V, rhs: &[T(TokenKind::Comma)] },
+];
+
+/// `S → V x`, `V → x`, `V → ε`: after `V → ε` comes the identifier `V → x` starts with.
+const FIRST_FOLLOW: &[Production] = &[
+    Production { lhs: NT::S, rhs: &[N(NT::V), T(TokenKind::Variable)] },
+    Production { lhs: NT::V, rhs: &[T(TokenKind::Variable)] },
+    Production { lhs: NT::V, rhs: &[] },
+];
+
+fn conflicts_of(productions: &'static [Production]) -> Vec<Conflict> {
+    return conflicts(productions, &Sets::compute(productions, NT::S));
+}
+
+#[test]
+fn the_grammar_is_ll1() {
+    assert_eq!(conflicts(PRODUCTIONS, sets()), []);
+}
+
+#[test]
+fn first_first() {
+    let found = conflicts_of(FIRST_FIRST);
+    let expected = Conflict { kind: ConflictKind::FirstFirst, token: TokenKind::Variable, productions: (&FIRST_FIRST[0], &FIRST_FIRST[1]) };
+    assert_eq!(found, [expected]);
+    assert_eq!(found[0].productions.0.lhs, NT::S);
+    assert_eq!(found[0].to_string(), r"FIRST/FIRST conflict in S on \String: `S → \String V` and `S → \String`");
+}
+
+#[test]
+fn first_follow() {
+    let found = conflicts_of(FIRST_FOLLOW);
+    let expected = Conflict { kind: ConflictKind::FirstFollow, token: TokenKind::Variable, productions: (&FIRST_FOLLOW[1], &FIRST_FOLLOW[2]) };
+    assert_eq!(found, [expected]);
+    assert_eq!(found[0].productions.0.lhs, NT::V);
+    assert_eq!(found[0].to_string(), r"FIRST/FOLLOW conflict in V on \String: `V → \String` and `V → ε`");
+}
@@ -0,0 +1,53 @@
+#![allow(clippy::needless_return)]
+
+//! Lexing details that a `tests/golden` file cannot hold or pin down: NUL bytes, and
+//! which numbers are accepted.
+
+use lambda_parser::codes::codes;
+use lambda_parser::lexer::lexer::{tokenize, Constant, Lexer, Token};
+use lambda_parser::parser::parser::Parser;
+
+#[test]
+fn nul_byte_is_an_unexpected_character() {
+    let src = "lambda x: x\0 + 1";
+    let errors: Vec<_> = Lexer::get(src).filter_map(Result::err).collect();
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].0.code, Some(codes::UNEXPECTED_CHARACTER));
+    assert_eq!((errors[0].0.span.start, errors[0].0.span.end), (11, 12));
+    assert_eq!(errors[0].to_string(), "unexpected character `\\u{0}`");
+
+    // Lexing goes on after it.
+    let tokens: Vec<Token> = tokenize(src).into_iter().map(|t| t.token).collect();
+    assert_eq!(tokens.last(), Some(&Token::Const(Constant::Number("1"))), "{:?}", tokens);
+
+    let mut parser = Parser::get("lambda x: x\0 $$$ ((( garbage");
+    parser.parse();
+    assert!(parser.diagnostics.len() > 1, "{:?}", parser.diagnostics);
+}
+
+/// Diagnostics of lexing `lambda: NUMBER`, as codes.
+fn number_errors(number: &str) -> Vec<&'static str> {
+    let src = format!("lambda: {}", number);
+    return Lexer::get(&src).filter_map(Result::err).map(|e| e.0.code.unwrap_or("-")).collect();
+}
+
+#[test]
+fn numbers_are_decimal_integers() {
+    for number in ["0", "00", "7", "42", "100"] {
+        assert_eq!(number_errors(number), Vec::<&str>::new(), "{}", number);
+    }
+    for number in ["01", "007", "1abc", "1_000", "0x1F", "1e5"] {
+        assert_eq!(number_errors(number), [codes::INVALID_NUMBER], "{}", number);
+    }
+
+    // The word stays one number, so parsing goes on after it.
+    let tokens: Vec<Token> = tokenize("lambda: 1abc + 01").into_iter().map(|t| t.token).collect();
+    assert_eq!(tokens[2], Token::Const(Constant::Number("1abc")));
+    assert_eq!(tokens[4], Token::Const(Constant::Number("01")));
+
+    let errors: Vec<_> = Lexer::get("lambda: 007").filter_map(Result::err).collect();
+    assert_eq!(errors[0].to_string(), "leading zeros in decimal integer literals are not permitted");
+    let suggestion = &errors[0].0.suggestions[0];
+    assert_eq!(suggestion.replacement, "7");
+    assert_eq!(Lexer::get("lambda: 1abc").find_map(Result::err).unwrap().to_string(), "invalid decimal literal");
+}
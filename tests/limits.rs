@@ -0,0 +1,126 @@
+#![allow(clippy::needless_return)]
+
+//! Inputs at and past the limits of `ParseLimits`.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use lambda_parser::codes::codes;
+use lambda_parser::limits::limits::{LimitExceeded, ParseLimits, DEFAULT_MAX_DEPTH};
+use lambda_parser::lower::lower::lower;
+use lambda_parser::parser::parser::Parser;
+use lambda_parser::pratt::pratt::PrattParser;
+
+/// Runs `f` on a thread with a 2 MiB stack, the default of spawned threads.
+fn on_small_stack(f: impl FnOnce() + Send + 'static) {
+    thread::Builder::new().stack_size(2 << 20).spawn(f).unwrap().join().unwrap();
+}
+
+#[test]
+fn nesting_at_the_limit_lowers_on_a_small_stack() {
+    on_small_stack(|| {
+        let depth = DEFAULT_MAX_DEPTH - 1;
+        let src = format!("lambda x: {}x{}", "(".repeat(depth), ")".repeat(depth));
+        let mut parser = Parser::get(&src);
+        let root = parser.parse();
+        assert!(parser.diagnostics.is_empty(), "{:?}", parser.diagnostics);
+        let ast = lower(&parser, root).ast;
+        assert_eq!(ast.to_string(), "(lambda (x) x)");
+
+        let src = format!("{}x", "lambda x: ".repeat(DEFAULT_MAX_DEPTH));
+        let mut parser = Parser::get(&src);
+        let root = parser.parse();
+        assert!(parser.diagnostics.is_empty(), "{:?}", parser.diagnostics);
+        assert_eq!(lower(&parser, root).ast.len(), DEFAULT_MAX_DEPTH + 1);
+    });
+}
+
+/// Codes of the diagnostics of every parser on `src`, after checking that they agree
+/// on the limit that stopped them.
+fn codes_with(src: &str, limits: ParseLimits, exceeded: Option<LimitExceeded>) -> Vec<&'static str> {
+    let mut parser = Parser::with_limits(src, limits);
+    parser.parse();
+    assert_eq!(parser.exceeded(), exceeded);
+    let mut table = Parser::with_limits(src, limits);
+    table.parse_table();
+    assert_eq!(table.exceeded(), exceeded);
+    assert_eq!(parser.diagnostics, table.diagnostics);
+    let mut pratt = PrattParser::with_limits(src, limits);
+    pratt.parse();
+    assert_eq!(pratt.lex.exceeded, exceeded);
+    assert_eq!(parser.diagnostics, pratt.diagnostics);
+    return parser.diagnostics.iter().map(|d| d.code.unwrap_or("-")).collect();
+}
+
+fn codes(src: &str, exceeded: Option<LimitExceeded>) -> Vec<&'static str> {
+    return codes_with(src, ParseLimits::default(), exceeded);
+}
+
+#[test]
+fn deep_nesting_is_one_error() {
+    let depth = 100_000;
+    on_small_stack(move || {
+        let parens = format!("lambda x: {}x{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(codes(&parens, Some(LimitExceeded::Depth(DEFAULT_MAX_DEPTH))), [codes::TOO_DEEP]);
+        let unclosed = format!("lambda x: {}", "(".repeat(depth));
+        assert_eq!(codes(&unclosed, Some(LimitExceeded::Depth(DEFAULT_MAX_DEPTH))), [codes::TOO_DEEP]);
+        let lambdas = format!("{}x", "lambda x: ".repeat(depth));
+        assert_eq!(codes(&lambdas, Some(LimitExceeded::Depth(DEFAULT_MAX_DEPTH))), [codes::TOO_DEEP]);
+    });
+}
+
+#[test]
+fn long_chains_take_constant_stack() {
+    on_small_stack(|| {
+        let terms = 1_000_000;
+        let src = format!("lambda x: {}", vec!["x"; terms].join(" + "));
+        assert_eq!(codes(&src, None), Vec::<&str>::new());
+    });
+}
+
+const SUM: &str = "lambda x, y: x + y * (x - 1)";
+
+#[test]
+fn source_length_limit() {
+    let limits = ParseLimits { max_source_length: 10, ..ParseLimits::default() };
+    let exceeded = Some(LimitExceeded::SourceLength(10));
+    assert_eq!(codes_with(SUM, limits, exceeded), [codes::LIMIT_EXCEEDED]);
+    let limits = ParseLimits { max_source_length: SUM.len(), ..ParseLimits::default() };
+    assert_eq!(codes_with(SUM, limits, None), Vec::<&str>::new());
+}
+
+#[test]
+fn token_limit() {
+    let limits = ParseLimits { max_tokens: 5, ..ParseLimits::default() };
+    assert_eq!(codes_with(SUM, limits, Some(LimitExceeded::Tokens(5))), [codes::LIMIT_EXCEEDED]);
+    let limits = ParseLimits { max_tokens: 14, ..ParseLimits::default() };
+    assert_eq!(codes_with(SUM, limits, None), Vec::<&str>::new());
+}
+
+#[test]
+fn node_limit() {
+    // The Pratt parser counts the nodes of its AST, which has fewer.
+    let limits = ParseLimits { max_nodes: 20, ..ParseLimits::default() };
+    let mut parser = Parser::with_limits(SUM, limits);
+    parser.parse();
+    assert_eq!(parser.exceeded(), Some(LimitExceeded::Nodes(20)));
+    let codes: Vec<_> = parser.diagnostics.iter().map(|d| d.code).collect();
+    assert_eq!(codes, [Some(codes::LIMIT_EXCEEDED)]);
+    let mut table = Parser::with_limits(SUM, limits);
+    table.parse_table();
+    assert_eq!(table.exceeded(), Some(LimitExceeded::Nodes(20)));
+    assert_eq!(parser.diagnostics, table.diagnostics);
+
+    let limits = ParseLimits { max_nodes: 2, ..ParseLimits::default() };
+    let mut pratt = PrattParser::with_limits(SUM, limits);
+    pratt.parse();
+    assert_eq!(pratt.lex.exceeded, Some(LimitExceeded::Nodes(2)));
+    let codes: Vec<_> = pratt.diagnostics.iter().map(|d| d.code).collect();
+    assert_eq!(codes, [Some(codes::LIMIT_EXCEEDED)]);
+}
+
+#[test]
+fn deadline() {
+    let limits = ParseLimits { deadline: Some(Instant::now() - Duration::from_secs(1)), ..ParseLimits::default() };
+    assert_eq!(codes_with(SUM, limits, Some(LimitExceeded::Deadline)), [codes::LIMIT_EXCEEDED]);
+}
@@ -0,0 +1,83 @@
+#![allow(clippy::needless_return)]
+
+//! Text renderings: trees, whole when small and cut at a depth for long chains, and
+//! diagnostics as JSON.
+
+use lambda_parser::diagnostic::diagnostic::to_json;
+use lambda_parser::diagram::diagram::Diagram;
+use lambda_parser::lower::lower::lower;
+use lambda_parser::parser::parser::Parser;
+use lambda_parser::tree::tree::{render_ast, render_derivation, TreeOptions, DEFAULT_TREE_DEPTH};
+
+fn sum(terms: usize) -> String {
+    return format!("lambda: {}", vec!["x"; terms].join(" + "));
+}
+
+#[test]
+fn small_trees_are_whole() {
+    let mut parser = Parser::get("lambda x: not x or -x * 2");
+    let root = parser.parse();
+    let options = TreeOptions { spans: true, ..TreeOptions::default() };
+    assert_eq!(
+        render_ast(&lower(&parser, root).ast, &options),
+        "lambda x 0..25\n└── or 10..25\n    ├── not 10..15\n    │   └── x 14..15\n    └── * 19..25\n        ├── - 19..21\n        │   └── x 20..21\n        └── 2 24..25\n"
+    );
+    assert!(!render_derivation(&parser, root, &options).contains('…'));
+}
+
+#[test]
+fn deep_trees_are_cut() {
+    let mut parser = Parser::get("lambda x: x + 1 + 2");
+    let root = parser.parse();
+    let ast = lower(&parser, root).ast;
+    let options = TreeOptions { max_depth: 1, ascii: true, ..TreeOptions::default() };
+    assert_eq!(render_ast(&ast, &options), "lambda x\n`-- +\n    `-- ...\n");
+    let options = TreeOptions { max_depth: 0, ..TreeOptions::default() };
+    assert_eq!(render_ast(&ast, &options), "lambda x\n└── …\n");
+    assert_eq!(
+        Diagram::ast(&ast).plantuml(Some(1)),
+        "@startwbs\n*[#dae8fc] lambda x\n**[#dae8fc] +\n***[#ffffff] <U+2026>\n@endwbs\n"
+    );
+    // Export formats keep every node unless asked.
+    let whole = Diagram::ast(&ast).plantuml(None);
+    assert_eq!(whole.lines().count(), 2 + ast.len(), "{}", whole);
+    assert!(!whole.contains("<U+2026>"));
+    assert!(!Diagram::ast(&ast).mermaid().contains('…'));
+}
+
+#[test]
+fn long_chains_take_linear_space() {
+    let terms = 30_000;
+    let src = sum(terms);
+    let mut parser = Parser::get(&src);
+    let root = parser.parse();
+    let ast = lower(&parser, root).ast;
+    let options = TreeOptions::default();
+    for text in [render_derivation(&parser, root, &options), render_ast(&ast, &options)] {
+        assert!(text.contains("…\n"), "nothing was cut");
+        assert!(text.len() < 100 * src.len(), "{} bytes for a source of {}", text.len(), src.len());
+    }
+    for diagram in [Diagram::derivation(&parser, root, false), Diagram::ast(&ast)] {
+        let text = diagram.plantuml(Some(DEFAULT_TREE_DEPTH));
+        assert!(text.contains("<U+2026>"), "nothing was cut");
+        assert!(text.len() < 100 * src.len(), "{} bytes for a source of {}", text.len(), src.len());
+    }
+}
+
+#[test]
+fn json_columns_count_characters() {
+    // `é` takes two bytes: `=` is at byte 18, the 7th byte but the 6th character of its line.
+    let src = "lambda xé:\n  xé = 1";
+    let mut parser = Parser::get(src);
+    parser.parse();
+    assert_eq!(parser.diagnostics.len(), 1, "{:?}", parser.diagnostics);
+    assert_eq!(
+        to_json(&parser.diagnostics[0], src),
+        concat!(
+            r#"{"code":"LP0010","severity":"error","message":"expected `==`, found `=`","span":{"start":18,"end":19},"#,
+            r#""label":"expected `==`","secondary":[],"notes":["assignment is not an expression"],"help":[],"#,
+            r#""expected":["=="],"suggestions":[{"message":"use `==` to compare values","span":{"start":18,"end":19},"#,
+            r#""replacement":"==","applicability":"MachineApplicable"}],"line":2,"column":6,"end_line":2,"end_column":7}"#,
+        )
+    );
+}
@@ -0,0 +1,165 @@
+#![allow(clippy::needless_return)]
+
+//! The traits of `visit` on small trees, checked against the `Display` of the result,
+//! and on a tree too deep for recursion.
+
+use lambda_parser::ast::ast::{Ast, Node, NodeId, Symbol};
+use lambda_parser::lexer::lexer::Operations;
+use lambda_parser::pratt::pratt::PrattParser;
+use lambda_parser::visit::visit::{fold, visit, visit_mut, Fold, Visitor, VisitorMut};
+
+fn ast(src: &str) -> Ast {
+    let mut parser = PrattParser::get(src);
+    parser.parse();
+    assert!(parser.diagnostics.is_empty(), "{:?}", parser.diagnostics);
+    return parser.ast;
+}
+
+/// Counts the uses of every name, and writes the tree back out as `Display` does.
+#[derive(Default)]
+struct Names {
+    uses: Vec<(String, usize)>,
+    sexpr: String,
+}
+
+impl Names {
+    fn open(&mut self, label: &str) -> bool {
+        self.sexpr.push_str(&format!(" ({}", label));
+        return true;
+    }
+}
+
+impl Visitor for Names {
+    fn visit_lambda(&mut self, ast: &Ast, _id: NodeId, params: &[Symbol], _body: NodeId) -> bool {
+        let params: Vec<&str> = params.iter().map(|p| ast.name(*p)).collect();
+        return self.open(&format!("lambda ({})", params.join(" ")));
+    }
+
+    fn visit_bool_op(&mut self, _ast: &Ast, _id: NodeId, op: Operations, _values: &[NodeId]) -> bool {
+        return self.open(&op.to_string());
+    }
+
+    fn visit_compare(&mut self, _ast: &Ast, _id: NodeId, _left: NodeId, _comparators: &[NodeId]) -> bool {
+        return self.open("==");
+    }
+
+    fn visit_bin_op(&mut self, _ast: &Ast, _id: NodeId, _left: NodeId, op: Operations, _right: NodeId) -> bool {
+        return self.open(&op.to_string());
+    }
+
+    fn visit_unary_op(&mut self, _ast: &Ast, _id: NodeId, op: Operations, _operand: NodeId) -> bool {
+        return self.open(&op.to_string());
+    }
+
+    fn visit_name(&mut self, ast: &Ast, _id: NodeId, name: Symbol) {
+        let name = ast.name(name);
+        match self.uses.iter_mut().find(|(n, _)| n == name) {
+            Some((_, count)) => *count += 1,
+            None => self.uses.push((name.to_string(), 1)),
+        }
+    }
+
+    fn leave_node(&mut self, ast: &Ast, id: NodeId) {
+        if ast.children(id).is_empty() {
+            self.sexpr.push_str(&format!(" {}", ast.label(id)));
+        } else {
+            self.sexpr.push(')');
+        }
+    }
+}
+
+#[test]
+fn visitor_counts_names() {
+    let tree = ast("lambda x, y: x * y == -x or not (y + 1)");
+    let mut names = Names::default();
+    visit(&mut names, &tree);
+    assert_eq!(names.uses, [("x".to_string(), 2), ("y".to_string(), 2)]);
+    assert_eq!(names.sexpr.trim_start(), tree.to_string());
+}
+
+/// Renames a variable, parameters included.
+struct Rename<'a>(&'a str, &'a str);
+
+impl VisitorMut for Rename<'_> {
+    fn visit_lambda_mut(&mut self, ast: &mut Ast, id: NodeId) -> bool {
+        let Node::Lambda { params, body } = ast.node(id) else {
+            unreachable!();
+        };
+        let params: Vec<&str> = ast.params(params).iter().map(|p| ast.name(*p)).collect();
+        let params: Vec<String> = params.into_iter().map(|p| if p == self.0 { self.1 } else { p }.to_string()).collect();
+        let params: Vec<Symbol> = params.iter().map(|p| ast.intern(p)).collect();
+        let params = ast.param_list(&params);
+        ast.replace(id, Node::Lambda { params, body });
+        return true;
+    }
+
+    fn visit_leaf_mut(&mut self, ast: &mut Ast, id: NodeId) {
+        if let Node::Name(name) = ast.node(id) {
+            if ast.name(name) == self.0 {
+                let name = ast.intern(self.1);
+                ast.replace(id, Node::Name(name));
+            }
+        }
+    }
+}
+
+#[test]
+fn visitor_mut_renames() {
+    let mut tree = ast("lambda x, y: x + (lambda x: x * y)");
+    visit_mut(&mut Rename("x", "z"), &mut tree);
+    assert_eq!(tree.to_string(), "(lambda (z y) (+ z (lambda (z) (* z y))))");
+}
+
+/// Folds `+` and `*` of two numbers into their result.
+struct Constants;
+
+impl Fold for Constants {
+    fn fold_bin_op(&mut self, ast: &Ast, id: NodeId, left: NodeId, op: Operations, right: NodeId, out: &mut Ast) -> NodeId {
+        if let (Node::Number(a), Node::Number(b)) = (out.node(left), out.node(right)) {
+            let (a, b): (u64, u64) = (out.name(a).parse().unwrap(), out.name(b).parse().unwrap());
+            let value = match op {
+                Operations::Add => Some(a + b),
+                Operations::Mul => Some(a * b),
+                _ => None,
+            };
+            if let Some(value) = value {
+                let value = out.intern(&value.to_string());
+                return out.add(Node::Number(value), ast.span(id));
+            }
+        }
+        return out.add(Node::BinOp { left, op, right }, ast.span(id));
+    }
+}
+
+#[test]
+fn fold_rewrites() {
+    let tree = ast("lambda x: x + 2 * 3 + (1 + 1) * x - 4 // 2");
+    let folded = fold(&mut Constants, &tree);
+    assert_eq!(folded.to_string(), "(lambda (x) (- (+ (+ x 6) (* 2 x)) (// 4 2)))");
+    assert_eq!(tree.to_string(), "(lambda (x) (- (+ (+ x (* 2 3)) (* (+ 1 1) x)) (// 4 2)))");
+}
+
+struct Nothing;
+
+impl Visitor for Nothing {}
+
+impl VisitorMut for Nothing {}
+
+impl Fold for Nothing {}
+
+#[test]
+fn deep_trees() {
+    let terms = 200_000;
+    let src = format!("lambda: {}", vec!["x"; terms].join(" + "));
+    let mut tree = ast(&src);
+
+    let mut names = Names::default();
+    visit(&mut names, &tree);
+    assert_eq!(names.uses, [("x".to_string(), terms)]);
+    visit(&mut Nothing, &tree);
+    visit_mut(&mut Rename("x", "y"), &mut tree);
+    visit_mut(&mut Nothing, &mut tree);
+    let copy = fold(&mut Nothing, &tree);
+    assert!(copy == tree);
+    assert_eq!(copy.len(), tree.len());
+}